@@ -10,7 +10,8 @@ use glwindow::AppControl;
 fn main() -> Result<(), Box<dyn Error>> {
     glwindow::Window::<State, EventHandler, Renderer>::new()
         .set_title("glwindow example – press escape to quit")
-        .run(State {}, EventHandler {})
+        .run(State {}, EventHandler {})?;
+    Ok(())
 }
 
 pub struct State {}
@@ -58,9 +59,7 @@ pub struct Renderer {
     program: gl::types::GLuint,
 }
 
-impl glwindow::AppRenderer for Renderer {
-    type AppState = State;
-
+impl glwindow::AppRendererNew for Renderer {
     fn new(gl: gl::Gl) -> Self {
         unsafe {
             if let Some(renderer) = get_gl_string(&gl, gl::RENDERER) {
@@ -132,6 +131,10 @@ impl glwindow::AppRenderer for Renderer {
             }
         }
     }
+}
+
+impl glwindow::AppRenderer for Renderer {
+    type AppState = State;
 
     fn draw(&self, _state: &mut State) {
         unsafe {
@@ -29,10 +29,30 @@ fn main() {
 
     let dest = PathBuf::from(&env::var("OUT_DIR").unwrap());
 
+    // Extensions to generate bindings for on top of the core functions, comma separated like
+    // `GLWINDOW_GL_EXTENSIONS=GL_EXT_disjoint_timer_query,GL_OES_vertex_array_object`.
+    let extensions = env::var("GLWINDOW_GL_EXTENSIONS").unwrap_or_default();
+    let extensions: Vec<&str> = extensions
+        .split(',')
+        .map(str::trim)
+        .filter(|extension| !extension.is_empty())
+        .collect();
+
     let mut file = File::create(dest.join("gl_bindings.rs")).unwrap();
-    Registry::new(Api::Gles2, (4, 1), Profile::Core, Fallbacks::All, [])
-        .write_bindings(StructGenerator, &mut file)
-        .unwrap();
+    // Desktop GL names its struct `Gl` already, GLES `Gles2`.
+    let registry = if env::var_os("CARGO_FEATURE_GL46").is_some() {
+        Registry::new(Api::Gl, (4, 6), Profile::Core, Fallbacks::All, extensions)
+    } else {
+        Registry::new(
+            Api::Gles2,
+            (4, 1),
+            Profile::Core,
+            Fallbacks::All,
+            extensions,
+        )
+    };
+    registry.write_bindings(StructGenerator, &mut file).unwrap();
 
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=GLWINDOW_GL_EXTENSIONS");
 }
@@ -0,0 +1,60 @@
+// Keyboard shortcuts bound to actions of the app, so it doesn't have to match on key events
+// itself, see `Window::bind_action`.
+
+use winit::keyboard::{KeyCode, ModifiersState};
+
+/// A key combination bound to an action with [`crate::Window::bind_action`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Shortcut {
+    pub key: KeyCode,
+    pub modifiers: ModifiersState,
+}
+
+impl Shortcut {
+    pub fn new(modifiers: ModifiersState, key: KeyCode) -> Self {
+        Self { key, modifiers }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ActionBindings {
+    bindings: Vec<(u32, Shortcut)>,
+}
+
+impl ActionBindings {
+    pub(crate) fn bind(&mut self, id: u32, shortcut: Shortcut) {
+        if !self.bindings.contains(&(id, shortcut)) {
+            self.bindings.push((id, shortcut));
+        }
+    }
+
+    /// The first shortcut bound to two different actions, with both of them.
+    pub(crate) fn conflict(&self) -> Option<(Shortcut, u32, u32)> {
+        self.bindings
+            .iter()
+            .enumerate()
+            .find_map(|(i, &(id, shortcut))| {
+                self.bindings[i + 1..]
+                    .iter()
+                    .find(|(other, other_shortcut)| *other_shortcut == shortcut && *other != id)
+                    .map(|&(other, _)| (shortcut, id, other))
+            })
+    }
+
+    /// The action for a key pressed with exactly these modifiers.
+    pub(crate) fn action(&self, key: KeyCode, modifiers: ModifiersState) -> Option<u32> {
+        let shortcut = Shortcut::new(modifiers, key);
+        self.bindings
+            .iter()
+            .find(|(_, bound)| *bound == shortcut)
+            .map(|&(id, _)| id)
+    }
+
+    /// The first shortcut bound to `id`.
+    pub(crate) fn shortcut(&self, id: u32) -> Option<Shortcut> {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == id)
+            .map(|&(_, shortcut)| shortcut)
+    }
+}
@@ -0,0 +1,76 @@
+// Timing a fixed number of frames, for `Window::run_benchmark`.
+
+use std::time::{Duration, Instant};
+
+/// Frame timings from [`Window::run_benchmark`](crate::Window::run_benchmark).
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub struct BenchmarkReport {
+    /// The number of frames drawn, fewer than asked for if the app exited early.
+    pub frames: u32,
+    /// From the start of the first frame until the last one was swapped.
+    pub total: Duration,
+    pub mean: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    /// The median frame time.
+    pub p50: Duration,
+    pub p95: Duration,
+    pub p99: Duration,
+}
+
+pub(crate) struct Benchmark {
+    frames: u32,
+    frame_times: Vec<Duration>,
+    last_swap: Option<Instant>,
+}
+
+impl Benchmark {
+    pub(crate) fn new(frames: u32) -> Self {
+        Self {
+            frames,
+            frame_times: Vec::with_capacity(frames as usize),
+            last_swap: None,
+        }
+    }
+
+    /// Called when a frame is about to be drawn. The first frame is timed from here, all later
+    /// ones from the swap before.
+    pub(crate) fn frame_started(&mut self) {
+        if self.last_swap.is_none() {
+            self.last_swap = Some(Instant::now());
+        }
+    }
+
+    /// Called after swapping buffers, returns whether all frames are done.
+    pub(crate) fn frame_swapped(&mut self) -> bool {
+        let now = Instant::now();
+        if let Some(last_swap) = self.last_swap.replace(now) {
+            self.frame_times.push(now - last_swap);
+        }
+        self.frame_times.len() >= self.frames as usize
+    }
+
+    pub(crate) fn report(mut self) -> BenchmarkReport {
+        self.frame_times.sort_unstable();
+        let times = &self.frame_times;
+        let total: Duration = times.iter().sum();
+        let frames = times.len() as u32;
+        let percentile = |p: usize| {
+            times
+                .get((times.len() * p / 100).min(times.len().saturating_sub(1)))
+                .copied()
+                .unwrap_or_default()
+        };
+        BenchmarkReport {
+            frames,
+            total,
+            mean: total.checked_div(frames).unwrap_or_default(),
+            min: times.first().copied().unwrap_or_default(),
+            max: times.last().copied().unwrap_or_default(),
+            p50: percentile(50),
+            p95: percentile(95),
+            p99: percentile(99),
+        }
+    }
+}
@@ -0,0 +1,40 @@
+// Whether a compositor blends the window with what's behind it, the transparency of a config
+// is only visible with one.
+
+use winit::window::Window;
+
+/// Whether the window is composited. Only X11 can run without a compositor, everywhere else
+/// this is always the case.
+pub(crate) fn is_compositing(window: &Window) -> bool {
+    #[cfg(x11_platform)]
+    if let Some(compositing) = x11::is_compositing(window) {
+        return compositing;
+    }
+    let _ = window;
+    true
+}
+
+#[cfg(x11_platform)]
+mod x11 {
+    use std::ffi::{c_char, CString};
+
+    use raw_window_handle::{HasDisplayHandle, RawDisplayHandle};
+    use winit::window::Window;
+    use x11_dl::xlib::Xlib;
+
+    // `None` when the window isn't on X11.
+    pub(super) fn is_compositing(window: &Window) -> Option<bool> {
+        let RawDisplayHandle::Xlib(handle) = window.display_handle().ok()?.as_raw() else {
+            return None;
+        };
+        let display = handle.display?.as_ptr() as *mut _;
+        let xlib = Xlib::open().ok()?;
+
+        // A compositing manager owns the `_NET_WM_CM_S<screen>` selection, see the EWMH spec.
+        let name = CString::new(format!("_NET_WM_CM_S{}", handle.screen)).ok()?;
+        unsafe {
+            let atom = (xlib.XInternAtom)(display, name.as_ptr() as *const c_char, 0);
+            Some((xlib.XGetSelectionOwner)(display, atom) != 0)
+        }
+    }
+}
@@ -0,0 +1,95 @@
+// Keeping the cursor inside part of the window, for `AppControl::ConfineCursorToRect`. winit
+// can only confine to the whole window, so the cursor is warped back whenever it leaves.
+
+use winit::dpi::PhysicalPosition;
+use winit::error::ExternalError;
+use winit::window::Window;
+
+/// A rectangle in the window, in physical pixels from the top left corner.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Rect {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Self {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    // The closest position inside the rectangle.
+    fn clamp(&self, position: PhysicalPosition<f64>) -> PhysicalPosition<f64> {
+        let right = self.x as f64 + self.width.saturating_sub(1) as f64;
+        let bottom = self.y as f64 + self.height.saturating_sub(1) as f64;
+        PhysicalPosition::new(
+            position.x.clamp(self.x as f64, right),
+            position.y.clamp(self.y as f64, bottom),
+        )
+    }
+}
+
+pub(crate) struct CursorConfinement {
+    rect: Rect,
+    // The last position the cursor was seen at inside the rectangle.
+    last_inside: Option<PhysicalPosition<f64>>,
+    // Where the cursor was warped to, the move there is reported back like any other.
+    warped_to: Option<PhysicalPosition<f64>>,
+}
+
+impl CursorConfinement {
+    pub(crate) fn new(rect: Rect) -> Self {
+        Self {
+            rect,
+            last_inside: None,
+            warped_to: None,
+        }
+    }
+
+    /// Warp the cursor back into the rectangle if it moved out. Fails when the platform doesn't
+    /// allow warping the cursor.
+    pub(crate) fn cursor_moved(
+        &mut self,
+        window: &Window,
+        position: PhysicalPosition<f64>,
+    ) -> Result<(), ExternalError> {
+        if let Some(target) = self.warped_to.take() {
+            // Our own warp, or rounded close enough to it by the platform.
+            if (position.x - target.x).abs() <= 1. && (position.y - target.y).abs() <= 1. {
+                return Ok(());
+            }
+        }
+
+        let clamped = self.rect.clamp(position);
+        if clamped == position {
+            self.last_inside = Some(position);
+            return Ok(());
+        }
+        self.warp(window, clamped)
+    }
+
+    /// The cursor left the window, too fast for a move outside the rectangle to be seen.
+    pub(crate) fn cursor_left(&mut self, window: &Window) -> Result<(), ExternalError> {
+        match self.last_inside {
+            Some(position) => self.warp(window, position),
+            None => Ok(()),
+        }
+    }
+
+    fn warp(
+        &mut self,
+        window: &Window,
+        position: PhysicalPosition<f64>,
+    ) -> Result<(), ExternalError> {
+        window.set_cursor_position(position)?;
+        self.warped_to = Some(position);
+        self.last_inside = Some(position);
+        Ok(())
+    }
+}
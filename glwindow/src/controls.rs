@@ -0,0 +1,126 @@
+use glutin::context::PossiblyCurrentContext;
+use raw_window_handle::HasWindowHandle;
+use winit::event_loop::EventLoopProxy;
+use winit::window::{CursorIcon, Window};
+
+use crate::action::ActionBindings;
+use crate::handle::LoopEvent;
+use crate::{
+    GlBackend, GlWindowError, MonitorInfo, PresentMode, RedrawRequester, SelectedFormat, Shortcut,
+    WorkerContext,
+};
+
+/// Access to the live window from the event handler.
+///
+/// The window doesn't exist before the app was first resumed, and on Android while the app is
+/// suspended. Queries return `None` then.
+pub struct WindowControls<'a> {
+    pub(crate) window: Option<&'a Window>,
+    pub(crate) backend: Option<GlBackend>,
+    pub(crate) present_mode: Option<PresentMode>,
+    pub(crate) selected_format: Option<SelectedFormat>,
+    pub(crate) gl_context: Option<&'a PossiblyCurrentContext>,
+    pub(crate) actions: &'a ActionBindings,
+    pub(crate) proxy: &'a EventLoopProxy<LoopEvent>,
+}
+
+impl WindowControls<'_> {
+    /// The platform API the OpenGL context was created with.
+    pub fn backend(&self) -> Option<GlBackend> {
+        self.backend
+    }
+
+    /// The present mode in effect, which can differ from the requested one when that isn't
+    /// available. `None` if the driver rejected the swap interval.
+    pub fn present_mode(&self) -> Option<PresentMode> {
+        self.present_mode
+    }
+
+    /// What the platform provided for the window.
+    pub fn selected_format(&self) -> Option<SelectedFormat> {
+        self.selected_format
+    }
+
+    /// The current inner size of the window in physical pixels.
+    pub fn inner_size(&self) -> Option<(u32, u32)> {
+        let size = self.window?.inner_size();
+        Some((size.width, size.height))
+    }
+
+    /// Create a context sharing objects with the window's, for uploads on a worker thread.
+    ///
+    /// The worker context belongs to the current window context. When that is recreated, e.g.
+    /// by [`crate::AppControl::RecreateSurface`], objects are no longer shared with it.
+    pub fn create_worker_context(&self) -> Result<WorkerContext, GlWindowError> {
+        let context = self.gl_context.ok_or(GlWindowError::NoSurface)?;
+        let window_handle = self
+            .window
+            .and_then(|window| window.window_handle().ok())
+            .map(|handle| handle.as_raw());
+        WorkerContext::new(context, window_handle)
+    }
+
+    /// A way to ask for a frame later on, outside of the handler.
+    pub fn redraw_requester(&self) -> RedrawRequester {
+        RedrawRequester {
+            proxy: self.proxy.clone(),
+        }
+    }
+
+    /// The first shortcut bound to the action `id` with [`crate::Window::bind_action`], e.g. to
+    /// show it in a menu.
+    pub fn shortcut_for(&self, id: u32) -> Option<Shortcut> {
+        self.actions.shortcut(id)
+    }
+
+    /// Change the title of the window. A window recreated later, e.g. for
+    /// [`crate::AppControl::RecreateSurface`], starts with the one from
+    /// [`crate::Window::set_title`] again.
+    pub fn set_title(&self, title: &str) -> Result<(), GlWindowError> {
+        self.window
+            .ok_or(GlWindowError::NoSurface)?
+            .set_title(title);
+        Ok(())
+    }
+
+    /// Change how the cursor looks while it's over the window, e.g. to a text cursor over an
+    /// input field.
+    pub fn set_cursor_icon(&self, icon: CursorIcon) -> Result<(), GlWindowError> {
+        self.window
+            .ok_or(GlWindowError::NoSurface)?
+            .set_cursor(icon);
+        Ok(())
+    }
+
+    /// Switch fullscreen on or off, like returning [`crate::AppControl::SetFullscreen`]. It's
+    /// applied once the handler returns, and kept for a recreated window.
+    pub fn set_fullscreen(&self, fullscreen: bool) -> Result<(), GlWindowError> {
+        self.window.ok_or(GlWindowError::NoSurface)?;
+        self.proxy
+            .send_event(LoopEvent::SetFullscreen(fullscreen))
+            .map_err(|_| GlWindowError::LoopClosed)
+    }
+
+    /// The monitor the window is currently on, if the platform can tell.
+    pub fn current_monitor(&self) -> Option<MonitorInfo> {
+        self.window?
+            .current_monitor()
+            .map(|monitor| MonitorInfo::from(&monitor))
+    }
+
+    /// All monitors, in the order [`crate::Window::set_monitor`] and
+    /// [`crate::AppControl::SetMonitor`] take them. Empty without a window.
+    ///
+    /// There is no list before running: winit only has monitors for a running event loop, and
+    /// only one event loop per process.
+    pub fn available_monitors(&self) -> Vec<MonitorInfo> {
+        self.window
+            .map(|window| {
+                window
+                    .available_monitors()
+                    .map(|monitor| MonitorInfo::from(&monitor))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,91 @@
+// Creating the display and the first window, what `glutin_winit::DisplayBuilder` does, but with a
+// config picker that is allowed to find nothing.
+
+use glutin::config::{Config, ConfigTemplateBuilder};
+use glutin::display::{Display, DisplayApiPreference};
+use glutin::prelude::*;
+#[cfg(wgl_backend)]
+use raw_window_handle::HasWindowHandle;
+use raw_window_handle::{HasDisplayHandle, RawWindowHandle};
+use winit::event_loop::ActiveEventLoop;
+use winit::window::{Window, WindowAttributes};
+
+use crate::GlWindowError;
+
+/// Create the display, pick a config from the ones matching `template` and create the window
+/// for it. [`GlWindowError::NoSuitableConfig`] when `pick` finds none.
+pub(crate) fn build_display(
+    event_loop: &ActiveEventLoop,
+    template: ConfigTemplateBuilder,
+    window_attributes: WindowAttributes,
+    pick: impl FnOnce(Box<dyn Iterator<Item = Config> + '_>) -> Option<Config>,
+) -> Result<(Window, Config), GlWindowError> {
+    // WGL needs the window before the display, and only gives configs compatible with it
+    // everything OpenGL has to offer.
+    #[cfg(wgl_backend)]
+    let window = event_loop
+        .create_window(window_attributes)
+        .map_err(GlWindowError::WindowCreation)?;
+    #[cfg(wgl_backend)]
+    let raw_window_handle = Some(
+        window
+            .window_handle()
+            .map_err(GlWindowError::NoWindowHandle)?
+            .as_raw(),
+    );
+    #[cfg(not(wgl_backend))]
+    let raw_window_handle = None;
+
+    let display = create_display(event_loop, raw_window_handle)?;
+
+    #[cfg(wgl_backend)]
+    let template = match raw_window_handle {
+        Some(handle) => template.compatible_with_native_window(handle),
+        None => template,
+    };
+
+    let gl_config = unsafe {
+        let configs = display
+            .find_configs(template.build())
+            .map_err(GlWindowError::ConfigSelection)?;
+        pick(configs).ok_or(GlWindowError::NoSuitableConfig)?
+    };
+
+    #[cfg(not(wgl_backend))]
+    let window = glutin_winit::finalize_window(event_loop, window_attributes, &gl_config)
+        .map_err(GlWindowError::WindowCreation)?;
+
+    Ok((window, gl_config))
+}
+
+// The system API first and EGL as the fallback, like `glutin_winit::ApiPreference::FallbackEgl`.
+fn create_display(
+    event_loop: &ActiveEventLoop,
+    _raw_window_handle: Option<RawWindowHandle>,
+) -> Result<Display, GlWindowError> {
+    #[cfg(egl_backend)]
+    let _preference = DisplayApiPreference::Egl;
+
+    #[cfg(glx_backend)]
+    let _preference =
+        DisplayApiPreference::Glx(Box::new(winit::platform::x11::register_xlib_error_hook));
+
+    #[cfg(cgl_backend)]
+    let _preference = DisplayApiPreference::Cgl;
+
+    #[cfg(wgl_backend)]
+    let _preference = DisplayApiPreference::Wgl(_raw_window_handle);
+
+    #[cfg(all(egl_backend, glx_backend))]
+    let _preference =
+        DisplayApiPreference::GlxThenEgl(Box::new(winit::platform::x11::register_xlib_error_hook));
+
+    #[cfg(all(wgl_backend, egl_backend))]
+    let _preference = DisplayApiPreference::WglThenEgl(_raw_window_handle);
+
+    let handle = event_loop
+        .display_handle()
+        .map_err(GlWindowError::NoWindowHandle)?
+        .as_raw();
+    unsafe { Display::new(handle, _preference) }.map_err(GlWindowError::DisplayCreation)
+}
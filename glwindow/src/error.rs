@@ -0,0 +1,182 @@
+use std::error::Error;
+use std::fmt;
+
+use raw_window_handle::HandleError;
+use winit::error::{EventLoopError, OsError};
+
+#[cfg(feature = "global-hotkey")]
+use crate::GlobalHotkey;
+use crate::Shortcut;
+
+/// Everything that can go wrong while running a window, from glwindow and the platform, or
+/// returned by the app's own handler.
+#[derive(Debug)]
+pub enum GlWindowError {
+    /// The RGBA data passed to `set_icon` doesn't match the icon dimensions.
+    InvalidIcon {
+        width: usize,
+        height: usize,
+        len: usize,
+    },
+    /// A size with a zero width or height was requested.
+    InvalidSize { width: usize, height: usize },
+    /// The minimum size is larger than the maximum size on either axis.
+    InvalidSizeLimits { min: (u32, u32), max: (u32, u32) },
+    /// The event loop couldn't be created, usually because there is no display server to
+    /// connect to, or failed while running.
+    EventLoop(EventLoopError),
+    /// The platform didn't create the window.
+    WindowCreation(OsError),
+    /// The OpenGL display, like the EGL or GLX connection, couldn't be set up.
+    DisplayCreation(glutin::error::Error),
+    /// The configs for the template couldn't be queried.
+    ConfigSelection(glutin::error::Error),
+    /// The window surface couldn't be created for the config.
+    SurfaceCreation(glutin::error::Error),
+    /// Making the context current or not current failed, or presenting a frame.
+    Context(glutin::error::Error),
+    /// There is no window surface to work with, like before the first resume, while
+    /// suspended or while minimized.
+    NoSurface,
+    /// The event loop a [`crate::GlWindowHandle`] belongs to has exited.
+    LoopClosed,
+    /// No OpenGL context could be created. Lists each API that was tried with the error it
+    /// failed with.
+    ContextCreation(Vec<(String, glutin::error::Error)>),
+    /// The window should be transparent, but none of the configs support that. See
+    /// [`crate::Window::require_transparency`].
+    NoTransparentConfig,
+    /// None of the configs the display offers match the requested framebuffer, more likely on
+    /// headless or otherwise constrained systems.
+    NoSuitableConfig,
+    /// The context for [`crate::WindowControls::create_worker_context`] couldn't be created.
+    WorkerContext(glutin::error::Error),
+    /// A platform handle of the window or its display isn't available, which the OpenGL
+    /// objects are created for.
+    NoWindowHandle(HandleError),
+    /// The same shortcut was bound to two different actions with [`crate::Window::bind_action`].
+    ConflictingShortcut {
+        shortcut: Shortcut,
+        actions: (u32, u32),
+    },
+    /// The framebuffer for [`crate::Window::set_internal_resolution`] couldn't be set up, e.g.
+    /// on a context older than OpenGL ES 3.
+    OffscreenFramebuffer(String),
+    /// A [`crate::DynWindow`] was run without a renderer, which it only gets from
+    /// [`crate::DynWindow::new_dyn`] or a `run_with` factory.
+    NoRenderer,
+    /// An error returned by the app's handler or renderer.
+    Handler(Box<dyn Error>),
+    /// The platform doesn't allow grabbing keys globally, like Wayland and macOS.
+    #[cfg(feature = "global-hotkey")]
+    HotkeysUnsupported,
+    /// The key of a global hotkey can't be registered.
+    #[cfg(feature = "global-hotkey")]
+    UnsupportedHotkey(GlobalHotkey),
+    /// A global hotkey is already registered by another application.
+    #[cfg(feature = "global-hotkey")]
+    HotkeyTaken(GlobalHotkey),
+}
+
+impl fmt::Display for GlWindowError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GlWindowError::InvalidIcon { width, height, len } => {
+                match width.checked_mul(*height).and_then(|n| n.checked_mul(4)) {
+                    Some(needed) => write!(
+                        f,
+                        "icon of {width}x{height} pixels needs {needed} bytes of RGBA data, got {len}"
+                    ),
+                    None => write!(f, "icon of {width}x{height} pixels is too large"),
+                }
+            }
+            GlWindowError::InvalidSizeLimits { min, max } => write!(
+                f,
+                "minimum size {}x{} is larger than the maximum size {}x{}",
+                min.0, min.1, max.0, max.1
+            ),
+            GlWindowError::InvalidSize { width, height } => {
+                write!(
+                    f,
+                    "invalid size {width}x{height}, both sides must be non-zero"
+                )
+            }
+            GlWindowError::EventLoop(err) => write!(f, "event loop error: {err}"),
+            GlWindowError::WindowCreation(err) => write!(f, "failed to create the window: {err}"),
+            GlWindowError::DisplayCreation(err) => {
+                write!(f, "failed to create the OpenGL display: {err}")
+            }
+            GlWindowError::ConfigSelection(err) => write!(f, "failed to query configs: {err}"),
+            GlWindowError::SurfaceCreation(err) => {
+                write!(f, "failed to create the window surface: {err}")
+            }
+            GlWindowError::Context(err) => write!(f, "OpenGL context error: {err}"),
+            GlWindowError::NoSurface => write!(f, "there is no window surface"),
+            GlWindowError::LoopClosed => write!(f, "the event loop has exited"),
+            GlWindowError::ContextCreation(attempts) => {
+                write!(f, "failed to create an OpenGL context")?;
+                for (api, err) in attempts {
+                    write!(f, "\n  {api}: {err}")?;
+                }
+                Ok(())
+            }
+            GlWindowError::NoTransparentConfig => {
+                write!(f, "there is no config supporting transparency")
+            }
+            GlWindowError::NoSuitableConfig => {
+                write!(f, "no config matches the requested framebuffer")
+            }
+            GlWindowError::OffscreenFramebuffer(reason) => {
+                write!(f, "no internal resolution: {reason}")
+            }
+            GlWindowError::NoRenderer => write!(f, "there is no renderer to run the window with"),
+            GlWindowError::WorkerContext(err) => {
+                write!(f, "failed to create a worker context: {err}")
+            }
+            GlWindowError::NoWindowHandle(err) => {
+                write!(f, "the window handle isn't available: {err}")
+            }
+            GlWindowError::ConflictingShortcut {
+                shortcut,
+                actions: (first, second),
+            } => write!(
+                f,
+                "shortcut {:?} + {:?} is bound to both action {first} and {second}",
+                shortcut.modifiers, shortcut.key
+            ),
+            // The app's own error, as it is.
+            GlWindowError::Handler(err) => err.fmt(f),
+            #[cfg(feature = "global-hotkey")]
+            GlWindowError::HotkeysUnsupported => {
+                write!(f, "global hotkeys aren't supported on this platform")
+            }
+            #[cfg(feature = "global-hotkey")]
+            GlWindowError::UnsupportedHotkey(hotkey) => {
+                write!(f, "{:?} can't be used for a global hotkey", hotkey.key)
+            }
+            #[cfg(feature = "global-hotkey")]
+            GlWindowError::HotkeyTaken(hotkey) => write!(
+                f,
+                "global hotkey {:?} + {:?} is already in use",
+                hotkey.modifiers, hotkey.key
+            ),
+        }
+    }
+}
+
+impl Error for GlWindowError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            GlWindowError::EventLoop(err) => Some(err),
+            GlWindowError::WindowCreation(err) => Some(err),
+            GlWindowError::DisplayCreation(err)
+            | GlWindowError::ConfigSelection(err)
+            | GlWindowError::SurfaceCreation(err)
+            | GlWindowError::Context(err) => Some(err),
+            GlWindowError::Handler(err) => err.source(),
+            GlWindowError::WorkerContext(err) => Some(err),
+            GlWindowError::NoWindowHandle(err) => Some(err),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,42 @@
+/// Framebuffer options that need a new config, and so a new surface and context, to change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FormatOptions {
+    /// Samples per pixel for multisampling, or the closest lower count available. `None` picks
+    /// the most available.
+    pub samples: Option<u8>,
+    /// Whether to render into an sRGB framebuffer, if there is a config for it. `None` leaves
+    /// it to the platform.
+    pub srgb: Option<bool>,
+}
+
+/// What the platform actually provided for the window, which isn't always what was asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct SelectedFormat {
+    /// Whether the driver accepted the swap interval of the present mode. When it didn't, frames
+    /// aren't paced by the display, so an app relying on vsync needs a frame limiter of its own.
+    pub swap_interval_applied: bool,
+    /// Samples per pixel of the framebuffer, `0` without multisampling.
+    pub samples: u8,
+    /// The sample count of [`FormatOptions::samples`], if there was one.
+    pub requested_samples: Option<u8>,
+    /// Whether the background actually shows through the window, for which it needs to be
+    /// transparent, get a config supporting that and be composited. Bare X11 without a
+    /// compositor shows black instead, so an app might want to clear to an opaque color.
+    pub transparent: bool,
+    /// Bits per channel of the color buffer, e.g. to dither when there are only 8 for red,
+    /// green and blue. All zero for luminance buffers.
+    pub red_bits: u8,
+    pub green_bits: u8,
+    pub blue_bits: u8,
+    pub alpha_bits: u8,
+}
+
+impl SelectedFormat {
+    /// Whether fewer samples than requested were available, so renderers can skip per-sample
+    /// work that assumes the requested count.
+    pub fn samples_downgraded(&self) -> bool {
+        self.requested_samples
+            .is_some_and(|requested| self.samples < requested)
+    }
+}
@@ -0,0 +1,101 @@
+use std::time::Duration;
+
+use winit::dpi::PhysicalPosition;
+use winit::window::Window;
+
+use crate::{gl, gl_clear_transparent, InputState, Rect, SelectedFormat, TouchState};
+
+/// Everything a renderer might need for drawing a frame, passed to
+/// [`crate::AppRenderer::draw_with_ctx`].
+#[non_exhaustive]
+pub struct FrameCtx<'a, S> {
+    /// The GL functions of the current context, the same the renderer was created with.
+    pub gl: &'a gl::Gl,
+    pub app_state: &'a mut S,
+    pub window: &'a Window,
+    /// Size of the framebuffer being drawn into, in pixels. This is the internal resolution
+    /// when one is set, the window size otherwise.
+    pub surface_size: (u32, u32),
+    /// Time since the previous frame, zero for the first one and while the clock is paused
+    /// with [`crate::AppControl::PauseClock`].
+    pub dt: Duration,
+    /// Frames drawn before this one, so the first frame is `0`.
+    pub frame_count: u64,
+    /// Time since the app started running, not counting the time the clock was paused.
+    pub uptime: Duration,
+    /// How far the frame is from the last fixed update to the next one, from `0.0` to `1.0`,
+    /// to interpolate with. Always `1.0` without [`crate::Window::set_fixed_timestep`].
+    pub alpha: f64,
+    /// The keyboard and mouse as of this frame, with what was pressed since the last one.
+    pub input: &'a InputState,
+    /// The fingers on the touchscreen as of this frame.
+    pub touch: &'a TouchState,
+    /// What the window's framebuffer actually provides.
+    pub format: SelectedFormat,
+    pub(crate) transparent: bool,
+    // Where the frame is shown in the window, from the bottom left like for GL.
+    pub(crate) target_rect: (i32, i32, i32, i32),
+}
+
+impl<S> FrameCtx<'_, S> {
+    /// Clear the framebuffer to `clear_color`, with depth and stencil, then draw the frame
+    /// with `draw`. The frame is presented once the renderer returns, a failure to do so ends
+    /// the app with the error instead of panicking.
+    ///
+    /// The color has straight alpha, it's premultiplied for a transparent window like with
+    /// [`crate::gl_clear_transparent`].
+    pub fn present<T>(
+        &mut self,
+        clear_color: [f32; 4],
+        draw: impl FnOnce(&gl::Gl, &mut S) -> T,
+    ) -> T {
+        let [r, g, b, a] = clear_color;
+        unsafe {
+            if self.transparent {
+                gl_clear_transparent(self.gl, r, g, b, a);
+            } else {
+                self.gl.ClearColor(r, g, b, a);
+                self.gl.Clear(gl::COLOR_BUFFER_BIT);
+            }
+            self.gl.Clear(gl::DEPTH_BUFFER_BIT | gl::STENCIL_BUFFER_BIT);
+        }
+        draw(self.gl, self.app_state)
+    }
+
+    /// The rectangle of the framebuffer to draw into, in pixels. With an internal resolution
+    /// the renderer draws into a framebuffer of that size, so this is always all of
+    /// [`FrameCtx::surface_size`], wherever the frame ends up in the window.
+    pub fn viewport(&self) -> Rect {
+        Rect::new(0, 0, self.surface_size.0, self.surface_size.1)
+    }
+
+    /// Set `glViewport` and `glScissor` to [`FrameCtx::viewport`].
+    pub fn apply_viewport(&self) {
+        let (width, height) = (self.surface_size.0 as i32, self.surface_size.1 as i32);
+        unsafe {
+            self.gl.Viewport(0, 0, width, height);
+            self.gl.Scissor(0, 0, width, height);
+        }
+    }
+
+    /// Where the frame is shown in the window, in physical pixels from the top left corner.
+    /// The whole window, unless an internal resolution is letterboxed into it.
+    pub fn presented_rect(&self) -> Rect {
+        let (x, y, width, height) = self.target_rect;
+        let window_height = self.window.inner_size().height as i32;
+        Rect::new(x, window_height - y - height, width as u32, height as u32)
+    }
+
+    /// Map a position in the window, like the cursor's, to framebuffer pixels from the top left
+    /// corner. `None` outside of [`FrameCtx::presented_rect`].
+    pub fn window_to_framebuffer(&self, position: PhysicalPosition<f64>) -> Option<(f64, f64)> {
+        let rect = self.presented_rect();
+        let x = (position.x - rect.x as f64) / rect.width as f64;
+        let y = (position.y - rect.y as f64) / rect.height as f64;
+        let inside = (0.0..1.0).contains(&x) && (0.0..1.0).contains(&y);
+        inside.then_some((
+            x * self.surface_size.0 as f64,
+            y * self.surface_size.1 as f64,
+        ))
+    }
+}
@@ -0,0 +1,135 @@
+// Pinch zoom and two finger pan, from the native trackpad gestures where the platform has them
+// and from two tracked touch points otherwise.
+
+use winit::dpi::PhysicalPosition;
+use winit::event::{TouchPhase, WindowEvent};
+
+use crate::TouchState;
+
+/// How far two fingers have to move before a touchscreen gesture is recognized, see
+/// [`crate::Window::set_gesture_thresholds`]. Native trackpad gestures are recognized by the
+/// platform already and always reported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GestureThresholds {
+    /// Relative change of the distance between the fingers to start zooming, `0.05` by
+    /// default.
+    pub zoom: f64,
+    /// Distance in physical pixels the point between the fingers has to move to start
+    /// panning, `10.0` by default.
+    pub pan: f64,
+}
+
+impl Default for GestureThresholds {
+    fn default() -> Self {
+        Self {
+            zoom: 0.05,
+            pan: 10.,
+        }
+    }
+}
+
+pub(crate) enum Gesture {
+    Zoom(f64),
+    Pan((f64, f64)),
+}
+
+// The two fingers of a touchscreen gesture.
+struct TwoFingers {
+    start_distance: f64,
+    start_center: PhysicalPosition<f64>,
+    distance: f64,
+    center: PhysicalPosition<f64>,
+    zooming: bool,
+    panning: bool,
+}
+
+pub(crate) struct GestureRecognizer {
+    thresholds: GestureThresholds,
+    touches: TouchState,
+    fingers: Option<TwoFingers>,
+}
+
+impl GestureRecognizer {
+    pub(crate) fn new(thresholds: GestureThresholds) -> Self {
+        Self {
+            thresholds,
+            touches: TouchState::new(),
+            fingers: None,
+        }
+    }
+
+    /// The gestures `event` continues, if any.
+    pub(crate) fn handle_event(&mut self, event: &WindowEvent) -> [Option<Gesture>; 2] {
+        match event {
+            // The magnification is relative, `0` means no change.
+            WindowEvent::PinchGesture { delta, .. } => [Some(Gesture::Zoom(1. + delta)), None],
+            WindowEvent::PanGesture { delta, .. } => {
+                [Some(Gesture::Pan((delta.x as f64, delta.y as f64))), None]
+            }
+            WindowEvent::Touch(touch) => {
+                self.touches.handle_event(event);
+                if touch.phase == TouchPhase::Moved {
+                    self.fingers_moved()
+                } else {
+                    // Start over whenever a finger is added or lifted.
+                    self.fingers = self.two_fingers().map(|(distance, center)| TwoFingers {
+                        start_distance: distance,
+                        start_center: center,
+                        distance,
+                        center,
+                        zooming: false,
+                        panning: false,
+                    });
+                    [None, None]
+                }
+            }
+            WindowEvent::Focused(false) => {
+                self.touches.handle_event(event);
+                self.fingers = None;
+                [None, None]
+            }
+            _ => [None, None],
+        }
+    }
+
+    fn fingers_moved(&mut self) -> [Option<Gesture>; 2] {
+        let two_fingers = self.two_fingers();
+        let (Some(fingers), Some((distance, center))) = (self.fingers.as_mut(), two_fingers) else {
+            return [None, None];
+        };
+
+        if !fingers.zooming {
+            let change = (distance / fingers.start_distance - 1.).abs();
+            fingers.zooming = change >= self.thresholds.zoom;
+        }
+        if !fingers.panning {
+            let dx = center.x - fingers.start_center.x;
+            let dy = center.y - fingers.start_center.y;
+            fingers.panning = dx.hypot(dy) >= self.thresholds.pan;
+        }
+
+        // Only the movement since the last event is reported, so nothing jumps once the
+        // threshold is crossed.
+        let zoom = (fingers.zooming && fingers.distance > 0.)
+            .then(|| Gesture::Zoom(distance / fingers.distance));
+        let pan = fingers.panning.then_some(Gesture::Pan((
+            center.x - fingers.center.x,
+            center.y - fingers.center.y,
+        )));
+        fingers.distance = distance;
+        fingers.center = center;
+        [zoom, pan]
+    }
+
+    // Distance between and center of the fingers, when there are exactly two.
+    fn two_fingers(&self) -> Option<(f64, PhysicalPosition<f64>)> {
+        if self.touches.touch_count() != 2 {
+            return None;
+        }
+        let mut touches = self.touches.touches();
+        let (a, b) = (touches.next()?.position, touches.next()?.position);
+        let distance = (a.x - b.x).hypot(a.y - b.y);
+        let center = PhysicalPosition::new((a.x + b.x) / 2., (a.y + b.y) / 2.);
+        Some((distance, center))
+    }
+}
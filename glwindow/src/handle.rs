@@ -0,0 +1,66 @@
+use std::any::Any;
+
+use winit::event_loop::EventLoopProxy;
+
+use crate::GlWindowError;
+
+// Everything that wakes up the event loop from outside of it.
+pub(crate) enum LoopEvent {
+    Exit,
+    Redraw,
+    // From `WindowControls::set_fullscreen`, which can't change the window information itself.
+    SetFullscreen(bool),
+    User(Box<dyn Any + Send>),
+    #[cfg(feature = "global-hotkey")]
+    Hotkey,
+}
+
+/// Controls a running window from outside of the event handler, e.g. from another thread.
+///
+/// Get one from [`crate::WindowRunner::handle`] before running the loop. On platforms where
+/// the event loop has to run on the main thread, like macOS, this handle is the only way to
+/// safely reach the window from other threads.
+#[derive(Clone)]
+pub struct GlWindowHandle {
+    pub(crate) proxy: EventLoopProxy<LoopEvent>,
+}
+
+impl GlWindowHandle {
+    /// Ask the app to exit, as if the handler returned [`crate::AppControl::Exit`].
+    pub fn exit(&self) -> Result<(), GlWindowError> {
+        self.send(LoopEvent::Exit)
+    }
+
+    /// Draw another frame soon.
+    pub fn request_redraw(&self) -> Result<(), GlWindowError> {
+        self.send(LoopEvent::Redraw)
+    }
+
+    /// Deliver `event` to [`crate::AppEventHandler::handle_user_event`] on the loop's thread.
+    pub fn send_event(&self, event: impl Any + Send) -> Result<(), GlWindowError> {
+        self.send(LoopEvent::User(Box::new(event)))
+    }
+
+    fn send(&self, event: LoopEvent) -> Result<(), GlWindowError> {
+        self.proxy
+            .send_event(event)
+            .map_err(|_| GlWindowError::LoopClosed)
+    }
+}
+
+/// Asks for a frame after the app changed its state, e.g. with
+/// [`crate::RedrawMode::OnDemand`]. Get one from [`crate::WindowControls::redraw_requester`];
+/// it can be stored in the app state and sent to other threads.
+#[derive(Clone)]
+pub struct RedrawRequester {
+    pub(crate) proxy: EventLoopProxy<LoopEvent>,
+}
+
+impl RedrawRequester {
+    /// Draw one frame soon. Any number of requests before that frame result in a single one.
+    pub fn request_redraw(&self) -> Result<(), GlWindowError> {
+        self.proxy
+            .send_event(LoopEvent::Redraw)
+            .map_err(|_| GlWindowError::LoopClosed)
+    }
+}
@@ -0,0 +1,443 @@
+// System wide hotkeys, delivered even while the window is unfocused. Each platform backend runs
+// on its own thread and wakes up the event loop whenever one was pressed.
+//
+// Supported on Windows (RegisterHotKey) and X11 (XGrabKey on the root window). Wayland and macOS
+// don't let applications grab keys globally, registering hotkeys there is an error.
+
+use std::sync::mpsc::{self, Receiver};
+
+use winit::keyboard::{KeyCode, ModifiersState};
+
+use crate::GlWindowError;
+
+/// A key combination registered with [`crate::Window::add_global_hotkey`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlobalHotkey {
+    pub key: KeyCode,
+    pub modifiers: ModifiersState,
+}
+
+impl GlobalHotkey {
+    pub fn new(modifiers: ModifiersState, key: KeyCode) -> Self {
+        Self { key, modifiers }
+    }
+}
+
+pub(crate) struct HotkeyListener {
+    pressed: Receiver<u32>,
+    // Stops listening when dropped.
+    _backend: backend::Backend,
+}
+
+impl HotkeyListener {
+    pub(crate) fn start(
+        hotkeys: Vec<(u32, GlobalHotkey)>,
+        wake_up: impl Fn() + Send + 'static,
+    ) -> Result<Self, GlWindowError> {
+        for (_, hotkey) in &hotkeys {
+            if key_codes(hotkey.key).is_none() {
+                return Err(GlWindowError::UnsupportedHotkey(*hotkey));
+            }
+        }
+
+        let (sender, pressed) = mpsc::channel();
+        let notify = move |id| {
+            if sender.send(id).is_ok() {
+                wake_up();
+            }
+        };
+        let backend = backend::Backend::start(hotkeys, notify)?;
+
+        Ok(Self {
+            pressed,
+            _backend: backend,
+        })
+    }
+
+    /// The next hotkey pressed since the last call, by id.
+    pub(crate) fn try_next(&self) -> Option<u32> {
+        self.pressed.try_recv().ok()
+    }
+}
+
+// Windows virtual key and X11 keysym for the keys that can be registered.
+fn key_codes(key: KeyCode) -> Option<(u32, u64)> {
+    const LETTERS: [KeyCode; 26] = [
+        KeyCode::KeyA,
+        KeyCode::KeyB,
+        KeyCode::KeyC,
+        KeyCode::KeyD,
+        KeyCode::KeyE,
+        KeyCode::KeyF,
+        KeyCode::KeyG,
+        KeyCode::KeyH,
+        KeyCode::KeyI,
+        KeyCode::KeyJ,
+        KeyCode::KeyK,
+        KeyCode::KeyL,
+        KeyCode::KeyM,
+        KeyCode::KeyN,
+        KeyCode::KeyO,
+        KeyCode::KeyP,
+        KeyCode::KeyQ,
+        KeyCode::KeyR,
+        KeyCode::KeyS,
+        KeyCode::KeyT,
+        KeyCode::KeyU,
+        KeyCode::KeyV,
+        KeyCode::KeyW,
+        KeyCode::KeyX,
+        KeyCode::KeyY,
+        KeyCode::KeyZ,
+    ];
+    const DIGITS: [KeyCode; 10] = [
+        KeyCode::Digit0,
+        KeyCode::Digit1,
+        KeyCode::Digit2,
+        KeyCode::Digit3,
+        KeyCode::Digit4,
+        KeyCode::Digit5,
+        KeyCode::Digit6,
+        KeyCode::Digit7,
+        KeyCode::Digit8,
+        KeyCode::Digit9,
+    ];
+    const FUNCTION_KEYS: [KeyCode; 12] = [
+        KeyCode::F1,
+        KeyCode::F2,
+        KeyCode::F3,
+        KeyCode::F4,
+        KeyCode::F5,
+        KeyCode::F6,
+        KeyCode::F7,
+        KeyCode::F8,
+        KeyCode::F9,
+        KeyCode::F10,
+        KeyCode::F11,
+        KeyCode::F12,
+    ];
+
+    if let Some(i) = LETTERS.iter().position(|&k| k == key) {
+        return Some((0x41 + i as u32, 0x61 + i as u64));
+    }
+    if let Some(i) = DIGITS.iter().position(|&k| k == key) {
+        return Some((0x30 + i as u32, 0x30 + i as u64));
+    }
+    if let Some(i) = FUNCTION_KEYS.iter().position(|&k| k == key) {
+        return Some((0x70 + i as u32, 0xffbe + i as u64));
+    }
+
+    let codes = match key {
+        KeyCode::Space => (0x20, 0x20),
+        KeyCode::Escape => (0x1b, 0xff1b),
+        KeyCode::Enter => (0x0d, 0xff0d),
+        KeyCode::Tab => (0x09, 0xff09),
+        KeyCode::Backspace => (0x08, 0xff08),
+        KeyCode::PrintScreen => (0x2c, 0xff61),
+        KeyCode::Insert => (0x2d, 0xff63),
+        KeyCode::Delete => (0x2e, 0xffff),
+        KeyCode::Home => (0x24, 0xff50),
+        KeyCode::End => (0x23, 0xff57),
+        KeyCode::PageUp => (0x21, 0xff55),
+        KeyCode::PageDown => (0x22, 0xff56),
+        KeyCode::ArrowLeft => (0x25, 0xff51),
+        KeyCode::ArrowUp => (0x26, 0xff52),
+        KeyCode::ArrowRight => (0x27, 0xff53),
+        KeyCode::ArrowDown => (0x28, 0xff54),
+        _ => return None,
+    };
+    Some(codes)
+}
+
+#[cfg(windows)]
+mod backend {
+    use std::sync::mpsc;
+    use std::thread::{self, JoinHandle};
+
+    use winit::keyboard::ModifiersState;
+
+    use super::{key_codes, GlobalHotkey};
+    use crate::GlWindowError;
+
+    const WM_QUIT: u32 = 0x0012;
+    const WM_HOTKEY: u32 = 0x0312;
+    const MOD_ALT: u32 = 0x1;
+    const MOD_CONTROL: u32 = 0x2;
+    const MOD_SHIFT: u32 = 0x4;
+    const MOD_WIN: u32 = 0x8;
+    const MOD_NOREPEAT: u32 = 0x4000;
+
+    #[repr(C)]
+    struct Msg {
+        hwnd: isize,
+        message: u32,
+        wparam: usize,
+        lparam: isize,
+        time: u32,
+        pt: [i32; 2],
+    }
+
+    #[link(name = "user32")]
+    extern "system" {
+        fn RegisterHotKey(hwnd: isize, id: i32, modifiers: u32, vk: u32) -> i32;
+        fn UnregisterHotKey(hwnd: isize, id: i32) -> i32;
+        fn GetMessageW(msg: *mut Msg, hwnd: isize, filter_min: u32, filter_max: u32) -> i32;
+        fn PostThreadMessageW(thread: u32, msg: u32, wparam: usize, lparam: isize) -> i32;
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetCurrentThreadId() -> u32;
+    }
+
+    pub(super) struct Backend {
+        thread_id: u32,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl Backend {
+        pub(super) fn start(
+            hotkeys: Vec<(u32, GlobalHotkey)>,
+            notify: impl Fn(u32) + Send + 'static,
+        ) -> Result<Self, GlWindowError> {
+            let (registered, result) = mpsc::channel();
+
+            // Hotkeys registered without a window are posted to the message queue of the
+            // registering thread, so that thread does nothing but wait for them.
+            let thread = thread::spawn(move || unsafe {
+                for (i, (id, hotkey)) in hotkeys.iter().enumerate() {
+                    let (vk, _) = key_codes(hotkey.key).unwrap();
+                    let modifiers = modifiers(hotkey.modifiers) | MOD_NOREPEAT;
+                    if RegisterHotKey(0, *id as i32, modifiers, vk) == 0 {
+                        for (id, _) in &hotkeys[..i] {
+                            UnregisterHotKey(0, *id as i32);
+                        }
+                        let _ = registered.send(Err(*hotkey));
+                        return;
+                    }
+                }
+                let _ = registered.send(Ok(GetCurrentThreadId()));
+
+                let mut msg = std::mem::zeroed::<Msg>();
+                while GetMessageW(&mut msg, 0, 0, 0) > 0 {
+                    if msg.message == WM_HOTKEY {
+                        notify(msg.wparam as u32);
+                    }
+                }
+
+                for (id, _) in &hotkeys {
+                    UnregisterHotKey(0, *id as i32);
+                }
+            });
+
+            match result.recv() {
+                Ok(Ok(thread_id)) => Ok(Self {
+                    thread_id,
+                    thread: Some(thread),
+                }),
+                // Most likely another application registered the same combination.
+                Ok(Err(hotkey)) => Err(GlWindowError::HotkeyTaken(hotkey)),
+                Err(_) => Err(GlWindowError::HotkeysUnsupported),
+            }
+        }
+    }
+
+    impl Drop for Backend {
+        fn drop(&mut self) {
+            unsafe { PostThreadMessageW(self.thread_id, WM_QUIT, 0, 0) };
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    fn modifiers(state: ModifiersState) -> u32 {
+        let mut modifiers = 0;
+        if state.shift_key() {
+            modifiers |= MOD_SHIFT;
+        }
+        if state.control_key() {
+            modifiers |= MOD_CONTROL;
+        }
+        if state.alt_key() {
+            modifiers |= MOD_ALT;
+        }
+        if state.super_key() {
+            modifiers |= MOD_WIN;
+        }
+        modifiers
+    }
+}
+
+#[cfg(all(not(windows), x11_platform))]
+mod backend {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread::{self, JoinHandle};
+    use std::time::Duration;
+    use std::{mem, ptr};
+
+    use winit::keyboard::ModifiersState;
+    use x11_dl::xlib::{self, Xlib};
+
+    use super::{key_codes, GlobalHotkey};
+    use crate::GlWindowError;
+
+    // The connection is only polled, so dropping the listener doesn't have to interrupt a
+    // blocking read.
+    const POLL_INTERVAL: Duration = Duration::from_millis(10);
+    const MODIFIER_MASK: u32 =
+        xlib::ShiftMask | xlib::ControlMask | xlib::Mod1Mask | xlib::Mod4Mask;
+    // Caps Lock and Num Lock, the grab has to be repeated with each combination of them.
+    const LOCK_MASKS: [u32; 4] = [
+        0,
+        xlib::LockMask,
+        xlib::Mod2Mask,
+        xlib::LockMask | xlib::Mod2Mask,
+    ];
+
+    pub(super) struct Backend {
+        stop: Arc<AtomicBool>,
+        thread: Option<JoinHandle<()>>,
+    }
+
+    impl Backend {
+        pub(super) fn start(
+            hotkeys: Vec<(u32, GlobalHotkey)>,
+            notify: impl Fn(u32) + Send + 'static,
+        ) -> Result<Self, GlWindowError> {
+            let xlib = Xlib::open().map_err(|_| GlWindowError::HotkeysUnsupported)?;
+            // Check for an X server up front, a Wayland session without Xwayland has none.
+            unsafe {
+                let display = (xlib.XOpenDisplay)(ptr::null());
+                if display.is_null() {
+                    return Err(GlWindowError::HotkeysUnsupported);
+                }
+                (xlib.XCloseDisplay)(display);
+            }
+
+            let stop = Arc::new(AtomicBool::new(false));
+            let thread = {
+                let stop = stop.clone();
+                thread::spawn(move || unsafe { listen(&xlib, &hotkeys, &stop, notify) })
+            };
+
+            Ok(Self {
+                stop,
+                thread: Some(thread),
+            })
+        }
+    }
+
+    impl Drop for Backend {
+        fn drop(&mut self) {
+            self.stop.store(true, Ordering::Relaxed);
+            if let Some(thread) = self.thread.take() {
+                let _ = thread.join();
+            }
+        }
+    }
+
+    // The listening thread has its own connection, Xlib connections aren't thread safe.
+    //
+    // A combination some other client grabbed already fails asynchronously with `BadAccess`,
+    // which ends up in the error handler winit installed and is otherwise ignored.
+    unsafe fn listen(
+        xlib: &Xlib,
+        hotkeys: &[(u32, GlobalHotkey)],
+        stop: &AtomicBool,
+        notify: impl Fn(u32),
+    ) {
+        let display = (xlib.XOpenDisplay)(ptr::null());
+        if display.is_null() {
+            return;
+        }
+        let root = (xlib.XDefaultRootWindow)(display);
+
+        let grabs: Vec<(u32, u32, u32)> = hotkeys
+            .iter()
+            .map(|(id, hotkey)| {
+                let (_, keysym) = key_codes(hotkey.key).unwrap();
+                let keycode = (xlib.XKeysymToKeycode)(display, keysym) as u32;
+                (*id, keycode, modifiers(hotkey.modifiers))
+            })
+            .collect();
+
+        for &(_, keycode, modifiers) in &grabs {
+            for lock in LOCK_MASKS {
+                (xlib.XGrabKey)(
+                    display,
+                    keycode as i32,
+                    modifiers | lock,
+                    root,
+                    xlib::False,
+                    xlib::GrabModeAsync,
+                    xlib::GrabModeAsync,
+                );
+            }
+        }
+        (xlib.XFlush)(display);
+
+        while !stop.load(Ordering::Relaxed) {
+            if (xlib.XPending)(display) == 0 {
+                thread::sleep(POLL_INTERVAL);
+                continue;
+            }
+
+            let mut event: xlib::XEvent = mem::zeroed();
+            (xlib.XNextEvent)(display, &mut event);
+            if event.get_type() != xlib::KeyPress {
+                continue;
+            }
+
+            let key = event.key;
+            let pressed = grabs.iter().find(|(_, keycode, modifiers)| {
+                key.keycode == *keycode && key.state & MODIFIER_MASK == *modifiers
+            });
+            if let Some(&(id, _, _)) = pressed {
+                notify(id);
+            }
+        }
+
+        for &(_, keycode, modifiers) in &grabs {
+            for lock in LOCK_MASKS {
+                (xlib.XUngrabKey)(display, keycode as i32, modifiers | lock, root);
+            }
+        }
+        (xlib.XCloseDisplay)(display);
+    }
+
+    fn modifiers(state: ModifiersState) -> u32 {
+        let mut modifiers = 0;
+        if state.shift_key() {
+            modifiers |= xlib::ShiftMask;
+        }
+        if state.control_key() {
+            modifiers |= xlib::ControlMask;
+        }
+        if state.alt_key() {
+            modifiers |= xlib::Mod1Mask;
+        }
+        if state.super_key() {
+            modifiers |= xlib::Mod4Mask;
+        }
+        modifiers
+    }
+}
+
+#[cfg(not(any(windows, x11_platform)))]
+mod backend {
+    use super::GlobalHotkey;
+    use crate::GlWindowError;
+
+    pub(super) struct Backend;
+
+    impl Backend {
+        pub(super) fn start(
+            _hotkeys: Vec<(u32, GlobalHotkey)>,
+            _notify: impl Fn(u32) + Send + 'static,
+        ) -> Result<Self, GlWindowError> {
+            Err(GlWindowError::HotkeysUnsupported)
+        }
+    }
+}
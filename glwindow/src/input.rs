@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+
+use winit::dpi::PhysicalPosition;
+use winit::event::{ElementState, KeyEvent, MouseButton, WindowEvent};
+use winit::keyboard::{KeyCode, ModifiersState, PhysicalKey};
+
+/// Keeps track of which keys are held down, fed from the window events.
+///
+/// Keys are tracked by their physical position ([`KeyCode`]), so bindings like WASD stay in
+/// place on keyboard layouts other than QWERTY. Use the `logical_key` of the event for text and
+/// shortcuts that should follow the layout.
+///
+/// The renderer gets the state as of drawing each frame in [`crate::FrameCtx::input`], with
+/// everything pressed or released since the frame before, like an immediate mode UI needs.
+#[derive(Debug, Clone, Default)]
+pub struct InputState {
+    held_keys: HashSet<KeyCode>,
+    pressed_keys: HashSet<KeyCode>,
+    released_keys: HashSet<KeyCode>,
+    held_buttons: HashSet<MouseButton>,
+    pressed_buttons: HashSet<MouseButton>,
+    released_buttons: HashSet<MouseButton>,
+    modifiers: ModifiersState,
+    cursor_position: Option<PhysicalPosition<f64>>,
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the state from an event, call this for every event the handler receives.
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(code),
+                        state,
+                        repeat,
+                        ..
+                    },
+                ..
+            } => match state {
+                ElementState::Pressed => {
+                    self.held_keys.insert(*code);
+                    if !repeat {
+                        self.pressed_keys.insert(*code);
+                    }
+                }
+                ElementState::Released => {
+                    self.held_keys.remove(code);
+                    self.released_keys.insert(*code);
+                }
+            },
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => {
+                    self.held_buttons.insert(*button);
+                    self.pressed_buttons.insert(*button);
+                }
+                ElementState::Released => {
+                    self.held_buttons.remove(button);
+                    self.released_buttons.insert(*button);
+                }
+            },
+            WindowEvent::ModifiersChanged(modifiers) => self.modifiers = modifiers.state(),
+            WindowEvent::CursorMoved { position, .. } => self.cursor_position = Some(*position),
+            WindowEvent::CursorLeft { .. } => self.cursor_position = None,
+            // Releases happening while unfocused are never delivered.
+            WindowEvent::Focused(false) => {
+                self.held_keys.clear();
+                self.held_buttons.clear();
+                self.modifiers = ModifiersState::empty();
+            }
+            _ => (),
+        }
+    }
+
+    /// Forget what was pressed and released so far, for the next frame. The state passed to
+    /// the renderer is reset after each frame already, this is for an `InputState` of your own.
+    pub fn end_frame(&mut self) {
+        self.pressed_keys.clear();
+        self.released_keys.clear();
+        self.pressed_buttons.clear();
+        self.released_buttons.clear();
+    }
+
+    pub fn is_key_held(&self, key: KeyCode) -> bool {
+        self.held_keys.contains(&key)
+    }
+
+    pub fn held_keys(&self) -> impl Iterator<Item = KeyCode> + '_ {
+        self.held_keys.iter().copied()
+    }
+
+    /// Whether `key` went down since the last [`InputState::end_frame`], key repeat excluded.
+    pub fn is_key_just_pressed(&self, key: KeyCode) -> bool {
+        self.pressed_keys.contains(&key)
+    }
+
+    /// Whether `key` was let go since the last [`InputState::end_frame`].
+    pub fn is_key_just_released(&self, key: KeyCode) -> bool {
+        self.released_keys.contains(&key)
+    }
+
+    pub fn is_button_held(&self, button: MouseButton) -> bool {
+        self.held_buttons.contains(&button)
+    }
+
+    pub fn is_button_just_pressed(&self, button: MouseButton) -> bool {
+        self.pressed_buttons.contains(&button)
+    }
+
+    pub fn is_button_just_released(&self, button: MouseButton) -> bool {
+        self.released_buttons.contains(&button)
+    }
+
+    pub fn modifiers(&self) -> ModifiersState {
+        self.modifiers
+    }
+
+    /// Where the cursor is in the window, in physical pixels. `None` while it's outside.
+    pub fn cursor_position(&self) -> Option<PhysicalPosition<f64>> {
+        self.cursor_position
+    }
+}
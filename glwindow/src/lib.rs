@@ -1,63 +1,143 @@
+use std::any::Any;
 use std::error::Error;
 use std::ffi::CString;
 use std::num::NonZeroU32;
+#[cfg(feature = "screenshot")]
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 
-use raw_window_handle::HasWindowHandle;
+use raw_window_handle::{HasDisplayHandle, HasWindowHandle};
 use winit::application::ApplicationHandler;
-use winit::dpi::PhysicalSize;
-use winit::event::WindowEvent;
+use winit::dpi::{LogicalSize, PhysicalPosition, PhysicalSize};
+use winit::error::ExternalError;
+use winit::event::{ElementState, KeyEvent, WindowEvent};
 use winit::event_loop::ActiveEventLoop;
-use winit::event_loop::EventLoop;
+use winit::event_loop::ControlFlow;
+use winit::event_loop::{EventLoop, EventLoopProxy};
+use winit::monitor::MonitorHandle;
 use winit::window::{self, CursorGrabMode, Icon, WindowAttributes};
 
-use glutin::config::{Config, ConfigTemplateBuilder, GetGlConfig};
+use glutin::config::{ColorBufferType, GetGlConfig};
 use glutin::context::{
-    ContextApi, ContextAttributesBuilder, NotCurrentContext, PossiblyCurrentContext, Version,
+    ContextApi, ContextAttributesBuilder, GlProfile, NotCurrentContext, PossiblyCurrentContext,
+    Version,
 };
 use glutin::display::GetGlDisplay;
+use glutin::error::ErrorKind;
 use glutin::prelude::*;
-use glutin::surface::{Surface, SwapInterval, WindowSurface};
+use glutin::surface::Surface;
 
-use glutin_winit::{DisplayBuilder, GlWindow};
+use glutin_winit::GlWindow;
 
+pub use glutin::config::{Config, ConfigTemplateBuilder};
 pub use glutin::display::GlDisplay;
+pub use glutin::surface::{SurfaceAttributesBuilder, WindowSurface};
+pub use raw_window_handle::{RawDisplayHandle, RawWindowHandle};
+pub use winit::dpi;
 pub use winit::event;
 pub use winit::keyboard;
+pub use winit::keyboard::{KeyCode, PhysicalKey};
+pub use winit::window::{CursorIcon, WindowButtons};
 
+/// OpenGL bindings, for OpenGL ES or with the `gl46` feature desktop OpenGL.
+///
+/// Only core functions are included. Extensions are added by listing them in the
+/// `GLWINDOW_GL_EXTENSIONS` environment variable at build time, comma separated, e.g.
+/// `GLWINDOW_GL_EXTENSIONS=GL_EXT_disjoint_timer_query,GL_OES_vertex_array_object`. Whether
+/// the driver actually supports one is checked with `is_loaded` on its functions.
 pub mod gl {
     #![allow(clippy::all)]
     include!(concat!(env!("OUT_DIR"), "/gl_bindings.rs"));
 
+    #[cfg(not(feature = "gl46"))]
     pub use Gles2 as Gl;
 }
 
-impl<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>> ApplicationHandler
-    for App<S, H, R>
+mod action;
+mod benchmark;
+mod compositor;
+mod confine;
+mod controls;
+mod display;
+mod error;
+mod format;
+mod frame;
+mod gesture;
+mod handle;
+#[cfg(feature = "global-hotkey")]
+mod hotkey;
+mod input;
+mod monitor;
+mod offscreen;
+mod power;
+mod present;
+#[cfg(feature = "screenshot")]
+mod recording;
+#[cfg(feature = "screenshot")]
+mod screenshot;
+mod sticky;
+mod taskbar;
+mod touch;
+mod worker;
+
+pub use action::Shortcut;
+pub use benchmark::BenchmarkReport;
+pub use confine::Rect;
+pub use controls::WindowControls;
+pub use error::GlWindowError;
+pub use format::{FormatOptions, SelectedFormat};
+pub use frame::FrameCtx;
+pub use gesture::GestureThresholds;
+pub use handle::{GlWindowHandle, RedrawRequester};
+#[cfg(feature = "global-hotkey")]
+pub use hotkey::GlobalHotkey;
+pub use input::InputState;
+pub use monitor::{MonitorInfo, VideoModeInfo};
+pub use present::{PresentMode, Vsync};
+pub use touch::{TouchPoint, TouchState};
+pub use worker::{CurrentWorkerContext, WorkerContext};
+
+use action::ActionBindings;
+use benchmark::Benchmark;
+use confine::CursorConfinement;
+use gesture::{Gesture, GestureRecognizer};
+use handle::LoopEvent;
+use offscreen::Offscreen;
+use power::PowerMonitor;
+use taskbar::TaskbarProgress;
+
+impl<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>>
+    ApplicationHandler<LoopEvent> for App<S, H, R>
 {
     fn resumed(&mut self, event_loop: &ActiveEventLoop) {
-        let (window, gl_config) = match &self.gl_display {
+        let (window, gl_config, gl_surface) = match &self.gl_display {
             // We just created the event loop, so initialize the display, pick the config, and
             // create the context.
-            GlDisplayCreationState::Builder(display_builder) => {
-                let (window, gl_config) = match display_builder.clone().build(
+            GlDisplayCreationState::Uninit => {
+                let info = &self.window_info;
+                let (window, gl_config) = match display::build_display(
                     event_loop,
                     self.template.clone(),
-                    gl_config_picker,
+                    window_attributes(event_loop, info),
+                    |configs| pick_config(configs, info),
                 ) {
-                    Ok((window, gl_config)) => {
-                        let window = window.unwrap();
-                        window.set_cursor_visible(self.window_info.cursor_visible);
-                        if self.window_info.cursor_grabbed {
-                            window
-                                .set_cursor_grab(CursorGrabMode::Confined)
-                                .or_else(|_e| window.set_cursor_grab(CursorGrabMode::Locked))
-                                .unwrap();
-                        }
-                        (window, gl_config)
+                    Ok(built) => built,
+                    Err(err) => {
+                        self.fail(event_loop, err);
+                        return;
                     }
+                };
+
+                let (window, gl_config, gl_surface) = match create_surface_with_fallback(
+                    event_loop,
+                    window,
+                    gl_config,
+                    &self.template,
+                    &self.window_info,
+                ) {
+                    Ok(created) => created,
                     Err(err) => {
-                        self.exit_state = Err(err);
-                        event_loop.exit();
+                        self.fail(event_loop, err);
                         return;
                     }
                 };
@@ -67,97 +147,75 @@ impl<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>> Applicat
                 self.gl_display = GlDisplayCreationState::Init;
 
                 // Create gl context.
-                self.gl_context =
-                    Some(create_gl_context(&window, &gl_config).treat_as_possibly_current());
+                match create_gl_context(&window, &gl_config, &self.window_info) {
+                    Ok(context) => self.gl_context = Some(context.treat_as_possibly_current()),
+                    Err(err) => {
+                        self.fail(event_loop, err);
+                        return;
+                    }
+                }
 
-                (window, gl_config)
+                (window, gl_config, gl_surface)
             }
             GlDisplayCreationState::Init => {
-                println!("Recreating window in `resumed`");
-                // Pick the config which we already use for the context.
-                let gl_config = self.gl_context.as_ref().unwrap().config();
-                match glutin_winit::finalize_window(
+                log::debug!("Recreating window in `resumed`");
+                // Pick the config which we already use for the context. Without one it was lost
+                // on suspend, which was reported then.
+                let Some(gl_config) = self.gl_context.as_ref().map(|context| context.config())
+                else {
+                    return;
+                };
+                let window = match glutin_winit::finalize_window(
                     event_loop,
-                    window_attributes(&self.window_info),
+                    window_attributes(event_loop, &self.window_info),
                     &gl_config,
                 ) {
-                    Ok(window) => {
-                        window.set_cursor_visible(self.window_info.cursor_visible);
-                        if self.window_info.cursor_grabbed {
-                            window
-                                .set_cursor_grab(CursorGrabMode::Confined)
-                                .or_else(|_e| window.set_cursor_grab(CursorGrabMode::Locked))
-                                .unwrap();
-                        }
-                        (window, gl_config)
+                    Ok(window) => window,
+                    Err(err) => {
+                        self.fail(event_loop, GlWindowError::WindowCreation(err));
+                        return;
                     }
+                };
+
+                // The context is tied to the config, so there is no other one to fall back on.
+                match create_surface(&window, &gl_config, &self.window_info) {
+                    Ok(gl_surface) => (window, gl_config, gl_surface),
                     Err(err) => {
-                        self.exit_state = Err(err.into());
-                        event_loop.exit();
+                        self.fail(event_loop, err);
                         return;
                     }
                 }
             }
         };
 
-        let attrs = window
-            .build_surface_attributes(Default::default())
-            .expect("Failed to build surface attributes");
-        let gl_surface = unsafe {
-            gl_config
-                .display()
-                .create_window_surface(&gl_config, &attrs)
-                .unwrap()
-        };
-
-        // The context needs to be current for the Renderer to set up shaders and
-        // buffers. It also performs function loading, which needs a current context on
-        // WGL.
-        let gl_context = self.gl_context.as_ref().unwrap();
-        gl_context.make_current(&gl_surface).unwrap();
-
-        self.renderer.get_or_insert_with(|| {
-            let gl = gl::Gl::load_with(|symbol| {
-                let symbol = CString::new(symbol).unwrap();
-                gl_config
-                    .display()
-                    .get_proc_address(symbol.as_c_str())
-                    .cast()
-            });
-            R::new(gl)
-        });
-
-        // Try setting vsync.
-        if let Err(res) = gl_surface
-            .set_swap_interval(gl_context, SwapInterval::Wait(NonZeroU32::new(1).unwrap()))
-        {
-            eprintln!("Error setting vsync: {res:?}");
-        }
-
-        assert!(self
-            .gl_state
-            .replace(GlState { gl_surface, window })
-            .is_none());
+        self.finish_resume(event_loop, window, gl_config, gl_surface);
     }
 
-    fn suspended(&mut self, _event_loop: &ActiveEventLoop) {
+    fn suspended(&mut self, event_loop: &ActiveEventLoop) {
         // This event is only raised on Android, where the backing NativeWindow for a GL
         // Surface can appear and disappear at any moment.
-        println!("Android window removed");
+        log::debug!("Android window removed");
+
+        // Some drivers drop commands still queued when the context stops being current, which
+        // can leave a torn last frame.
+        if self.window_info.finish_on_suspend && self.gl_state.is_some() {
+            if let Some(gl) = self.gl.as_ref() {
+                unsafe { gl.Finish() };
+            }
+        }
 
         // Destroy the GL Surface and un-current the GL Context before ndk-glue releases
         // the window back to the system.
         self.gl_state = None;
 
         // Make context not current.
-        self.gl_context = Some(
-            self.gl_context
-                .take()
-                .unwrap()
-                .make_not_current()
-                .unwrap()
-                .treat_as_possibly_current(),
-        );
+        let Some(gl_context) = self.gl_context.take() else {
+            return;
+        };
+        match gl_context.make_not_current() {
+            Ok(gl_context) => self.gl_context = Some(gl_context.treat_as_possibly_current()),
+            Err(err) => self.fail(event_loop, GlWindowError::Context(err)),
+        }
     }
 
     fn window_event(
@@ -166,70 +224,417 @@ impl<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>> Applicat
         _window_id: winit::window::WindowId,
         event: WindowEvent,
     ) {
+        // Before anything else looks at the event, so a consumed one leaves no trace.
+        if let PreHandleResult::Consume = self.handler.pre_handle_event(&mut self.app_state, &event)
+        {
+            return;
+        }
+
+        if is_input_event(&event) {
+            self.last_input = Instant::now();
+        }
+        self.input.handle_event(&event);
+        self.touch.handle_event(&event);
+        for gesture in self.gestures.handle_event(&event).into_iter().flatten() {
+            self.dispatch_gesture(event_loop, gesture);
+        }
+
+        // A bound shortcut goes to `handle_action` instead, key repeat included so holding it
+        // doesn't leak presses. The release is passed on as usual.
+        if let WindowEvent::KeyboardInput {
+            event:
+                KeyEvent {
+                    physical_key: PhysicalKey::Code(code),
+                    state: ElementState::Pressed,
+                    repeat,
+                    ..
+                },
+            ..
+        } = &event
+        {
+            if let Some(id) = self
+                .window_info
+                .actions
+                .action(*code, self.input.modifiers())
+            {
+                if !repeat {
+                    let result = self.handler.handle_action(&mut self.app_state, id);
+                    match result {
+                        Ok(control) => self.apply_control(event_loop, control),
+                        Err(e) => self.fail(event_loop, GlWindowError::Handler(e)),
+                    }
+                }
+                return;
+            }
+        }
+
         match event {
             WindowEvent::Resized(size) if size.width != 0 && size.height != 0 => {
-                // Some platforms like EGL require resizing GL surface to update the size
-                // Notable platforms here are Wayland and macOS, other don't require it
-                // and the function is no-op, but it's wise to resize it for portability
-                // reasons.
-                if let Some(GlState {
-                    gl_surface,
-                    window: _,
-                }) = self.gl_state.as_ref()
+                self.seen_resize = true;
+                self.set_minimized(false);
+                let size = self.keep_aspect_ratio(size);
+                self.resize_surface(size);
+                // After the surface, so the handler can already rely on the new size.
+                self.dispatch_event(event_loop, WindowEvent::Resized(size));
+            }
+            WindowEvent::Resized(_) => {
+                // A zero sized surface means we got minimized, there is nothing to draw into
+                // until the window is restored.
+                self.set_minimized(true);
+                self.dispatch_event(event_loop, event);
+            }
+            WindowEvent::CloseRequested => {
+                // Declined, e.g. to ask about unsaved work first, as if it never happened.
+                if self.handler.on_close_requested(&mut self.app_state) {
+                    self.dispatch_event(event_loop, event);
+                }
+            }
+            WindowEvent::Occluded(occluded) => {
+                // Nothing drawn while fully covered would be seen, unless the app wants the
+                // frames anyway.
+                if !self.window_info.draw_while_occluded {
+                    self.set_occluded(occluded);
+                }
+                self.dispatch_event(event_loop, event);
+            }
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => {
+                // The logical size changes with the scale factor even if the physical size
+                // stays the same. A changed physical size arrives as a separate `Resized`.
+                if let Some(GlState { window, .. }) = self.gl_state.as_ref() {
+                    let size = window.inner_size();
+                    if size.width != 0 && size.height != 0 && self.offscreen.is_none() {
+                        let renderer: &mut R = self.renderer.as_mut().unwrap();
+                        renderer.surface_resized(SurfaceSize::new(size, scale_factor));
+                    }
+                }
+                self.dispatch_event(event_loop, event);
+            }
+            WindowEvent::Focused(focused) => {
+                // Keep trying on every focus gain, the grab is wanted until it worked once.
+                if focused && self.grab_pending {
+                    if let Some(GlState { window, .. }) = self.gl_state.as_ref() {
+                        match grab_cursor(window, self.window_info.mouselook) {
+                            Ok(()) => self.grab_pending = false,
+                            Err(err) => log::warn!("Failed to grab the cursor: {err}"),
+                        }
+                    }
+                }
+                if self.focused != focused {
+                    self.focused = focused;
+                    if let Some(renderer) = self.renderer.as_mut() {
+                        renderer.focus_changed(focused);
+                    }
+                }
+                self.dispatch_event(event_loop, event);
+            }
+            WindowEvent::CursorMoved { position, .. } => {
+                self.confine_cursor(|confinement, window| {
+                    confinement.cursor_moved(window, position)
+                });
+                self.dispatch_event(event_loop, event);
+            }
+            WindowEvent::CursorLeft { .. } => {
+                self.confine_cursor(|confinement, window| confinement.cursor_left(window));
+                self.dispatch_event(event_loop, event);
+            }
+            WindowEvent::RedrawRequested => {
+                // The first frame also when drawing from `about_to_wait`, some compositors ask
+                // for it before the loop gets there, and show a blank window until then.
+                let first_frame = self.frame_count == 0;
+                if (self.draws_on_redraw() || first_frame)
+                    && !self.hidden()
+                    && !self.waiting_for_size()
                 {
-                    let gl_context = self.gl_context.as_ref().unwrap();
-                    gl_surface.resize(
-                        gl_context,
-                        NonZeroU32::new(size.width).unwrap(),
-                        NonZeroU32::new(size.height).unwrap(),
-                    );
+                    self.render_frame(event_loop);
+                }
+                self.dispatch_event(event_loop, event);
+            }
+            event => self.dispatch_event(event_loop, event),
+        }
+    }
 
-                    let renderer: &mut R = self.renderer.as_mut().unwrap();
-                    renderer.resize(size.width as i32, size.height as i32);
+    fn user_event(&mut self, event_loop: &ActiveEventLoop, event: LoopEvent) {
+        match event {
+            LoopEvent::Exit => event_loop.exit(),
+            LoopEvent::Redraw => {
+                if let Some(GlState { window, .. }) = self.gl_state.as_ref() {
+                    window.request_redraw();
                 }
             }
-            event => match self.handler.handle_event(&mut self.app_state, event) {
-                Ok(AppControl::Continue) => (),
-                Ok(AppControl::Exit) => event_loop.exit(),
-                Err(e) => {
-                    self.exit_state = Err(e);
-                    event_loop.exit();
+            LoopEvent::SetFullscreen(fullscreen) => self.set_fullscreen(fullscreen),
+            LoopEvent::User(event) => {
+                match self.handler.handle_user_event(&mut self.app_state, event) {
+                    Ok(control) => self.apply_control(event_loop, control),
+                    Err(e) => {
+                        self.fail(event_loop, GlWindowError::Handler(e));
+                    }
                 }
-            },
+            }
+            #[cfg(feature = "global-hotkey")]
+            LoopEvent::Hotkey => {
+                let Some(hotkeys) = self.hotkeys.as_ref() else {
+                    return;
+                };
+
+                let pressed: Vec<u32> = std::iter::from_fn(|| hotkeys.try_next()).collect();
+                for id in pressed {
+                    match self.handler.handle_global_hotkey(&mut self.app_state, id) {
+                        Ok(control) => self.apply_control(event_loop, control),
+                        Err(e) => {
+                            self.fail(event_loop, GlWindowError::Handler(e));
+                            return;
+                        }
+                    }
+                }
+            }
         }
     }
 
     fn exiting(&mut self, _event_loop: &ActiveEventLoop) {
+        // Write out what was recorded so far while the context is still around.
+        #[cfg(feature = "screenshot")]
+        if self.gl_state.is_some() {
+            self.stop_recording(_event_loop, None);
+        }
+
+        // The context is only current while there is a surface, on Android it's gone already
+        // after a suspend.
+        if self.gl_state.is_some() {
+            if let Some(renderer) = self.renderer.as_mut() {
+                renderer.shutdown();
+            }
+        }
+
+        if let (Some(gl), Some(offscreen), Some(_)) = (
+            self.gl.as_ref(),
+            self.offscreen.take(),
+            self.gl_state.as_ref(),
+        ) {
+            unsafe { offscreen.delete(gl) };
+        }
+
         // NOTE: The handling below is only needed due to nvidia on Wayland to not crash
         // on exit due to nvidia driver touching the Wayland display from on
         // `exit` hook.
-        let _gl_display = self.gl_context.take().unwrap().display();
+        // There is no context when recreating it for a new format failed.
+        let _gl_display = self.gl_context.take().map(|context| context.display());
 
         // Clear the window.
         self.gl_state = None;
+
         #[cfg(egl_backend)]
-        #[allow(irrefutable_let_patterns)]
-        if let glutin::display::Display::Egl(display) = _gl_display {
-            unsafe {
-                display.terminate();
+        if let Some(glutin::display::Display::Egl(display)) = _gl_display {
+            if self.window_info.terminate_display_on_exit {
+                unsafe {
+                    display.terminate();
+                }
             }
         }
     }
 
-    fn about_to_wait(&mut self, _event_loop: &ActiveEventLoop) {
-        if let Some(GlState { gl_surface, window }) = self.gl_state.as_ref() {
-            let gl_context = self.gl_context.as_ref().unwrap();
-            let renderer = self.renderer.as_ref().unwrap();
-            renderer.draw(&mut self.app_state);
-            window.request_redraw();
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        if self.hidden() {
+            event_loop.set_control_flow(ControlFlow::Wait);
+            return;
+        }
+
+        if let Some(GlState { window, .. }) = self.gl_state.as_ref() {
+            if self.frame_count == 0 {
+                if self.waiting_for_size() {
+                    event_loop.set_control_flow(ControlFlow::WaitUntil(self.first_frame_deadline));
+                    return;
+                }
+                // Once the size is known, whichever mode there is.
+                window.request_redraw();
+            }
+
+            // Every other frame is up to the app.
+            if !self.window_info.auto_request_redraw {
+                event_loop.set_control_flow(ControlFlow::Wait);
+                return;
+            }
+
+            let interval = match self.window_info.loop_mode {
+                // Nothing to do until something asks for a redraw.
+                LoopMode::Wait => {
+                    event_loop.set_control_flow(ControlFlow::Wait);
+                    return;
+                }
+                LoopMode::WaitUntil(interval) => Some(interval),
+                LoopMode::Poll => {
+                    // Once there was no input for a while, drop down to the idle frame rate by
+                    // waking up on a timer instead of redrawing continuously.
+                    let idle_interval = self
+                        .window_info
+                        .idle_fps
+                        .filter(|_| self.last_input.elapsed() >= self.window_info.idle_delay)
+                        .map(|fps| Duration::from_secs(1) / fps.max(1));
+                    // On battery, cap to half the refresh rate, whichever is slower.
+                    let battery_interval = self
+                        .power
+                        .as_mut()
+                        .and_then(|power| power.frame_interval(window));
+                    // Without the swap interval vsync was asked for, limit to the refresh rate
+                    // instead of spinning.
+                    let vsync_interval = (self.present_mode.is_none()
+                        && self.window_info.present_mode != PresentMode::Immediate)
+                        .then(|| {
+                            present::refresh_interval(window) * self.window_info.vblanks.get()
+                        });
+                    [idle_interval, battery_interval, vsync_interval]
+                        .into_iter()
+                        .flatten()
+                        .max()
+                }
+            };
+            match interval {
+                Some(interval) => {
+                    let now = Instant::now();
+                    if now < self.next_frame {
+                        event_loop.set_control_flow(ControlFlow::WaitUntil(self.next_frame));
+                        return;
+                    }
+                    self.next_frame = now + interval;
+                    event_loop.set_control_flow(ControlFlow::WaitUntil(self.next_frame));
+                    if self.draws_on_redraw() {
+                        window.request_redraw();
+                    }
+                }
+                None => {
+                    event_loop.set_control_flow(ControlFlow::Wait);
+                    window.request_redraw();
+                }
+            }
+
+            if !self.draws_on_redraw() {
+                self.render_frame(event_loop);
+            }
+        }
+    }
+}
+
+fn create_surface(
+    window: &window::Window,
+    gl_config: &Config,
+    window_info: &WindowInformation,
+) -> Result<Surface<WindowSurface>, GlWindowError> {
+    let mut attrs = SurfaceAttributesBuilder::new().with_srgb(window_info.format.srgb);
+    if let Some(surface_attributes) = window_info.surface_attributes.as_ref() {
+        attrs = surface_attributes(attrs);
+    }
+    let attrs = window
+        .build_surface_attributes(attrs)
+        .map_err(GlWindowError::NoWindowHandle)?;
+    unsafe { gl_config.display().create_window_surface(gl_config, &attrs) }
+        .map_err(GlWindowError::SurfaceCreation)
+}
+
+// How long the first frame waits for the initial `Resized`.
+const FIRST_FRAME_TIMEOUT: Duration = Duration::from_millis(100);
+
+// Fixed updates run at most per frame, so a stall doesn't make every frame slower still.
+const MAX_FIXED_STEPS: u32 = 8;
+
+// How many configs to try before giving up on creating a surface.
+const SURFACE_ATTEMPTS: usize = 3;
+
+// Some drivers advertise configs they can't actually create a surface with, so pick another one
+// when that happens.
+fn create_surface_with_fallback(
+    event_loop: &ActiveEventLoop,
+    mut window: window::Window,
+    mut gl_config: Config,
+    template: &ConfigTemplateBuilder,
+    window_info: &WindowInformation,
+) -> Result<(window::Window, Config, Surface<WindowSurface>), GlWindowError> {
+    let mut failed = Vec::new();
+    loop {
+        // Another config can't fix this one, they're all no better.
+        if window_info.transparent
+            && window_info.require_transparency
+            && !gl_config.supports_transparency().unwrap_or(false)
+        {
+            return Err(GlWindowError::NoTransparentConfig);
+        }
 
-            gl_surface.swap_buffers(gl_context).unwrap();
+        let err = match create_surface(&window, &gl_config, window_info) {
+            Ok(gl_surface) => return Ok((window, gl_config, gl_surface)),
+            Err(err) => err,
+        };
+
+        failed.push(gl_config.clone());
+        if failed.len() >= SURFACE_ATTEMPTS {
+            return Err(err);
         }
+
+        let display = gl_config.display();
+        let configs = unsafe { display.find_configs(template.clone().build()) }
+            .map_err(GlWindowError::ConfigSelection)?;
+        let remaining = configs.filter(|config| !failed.contains(config));
+        let Some(next_config) = pick_config(Box::new(remaining), window_info) else {
+            return Err(err);
+        };
+
+        log::warn!("Failed to create surface, trying another config: {err}");
+        gl_config = next_config;
+
+        // The window can depend on the config, e.g. for the X11 visual, so it's recreated too.
+        drop(window);
+        window = glutin_winit::finalize_window(
+            event_loop,
+            window_attributes(event_loop, window_info),
+            &gl_config,
+        )
+        .map_err(GlWindowError::WindowCreation)?;
     }
 }
 
-fn create_gl_context(window: &window::Window, gl_config: &Config) -> NotCurrentContext {
-    let raw_window_handle = window.window_handle().ok().map(|wh| wh.as_raw());
+// Pick a config for the current format on an existing display, with a window and surface for it.
+fn create_with_new_config(
+    event_loop: &ActiveEventLoop,
+    display: &glutin::display::Display,
+    template: &ConfigTemplateBuilder,
+    window_info: &WindowInformation,
+) -> Result<(window::Window, Config, Surface<WindowSurface>), GlWindowError> {
+    let configs = unsafe { display.find_configs(template.clone().build()) }
+        .map_err(GlWindowError::ConfigSelection)?;
+    let gl_config = pick_config(configs, window_info).ok_or(GlWindowError::NoSuitableConfig)?;
+    let window = glutin_winit::finalize_window(
+        event_loop,
+        window_attributes(event_loop, window_info),
+        &gl_config,
+    )
+    .map_err(GlWindowError::WindowCreation)?;
+    create_surface_with_fallback(event_loop, window, gl_config, template, window_info)
+}
+
+fn create_gl_context(
+    window: &window::Window,
+    gl_config: &Config,
+    window_info: &WindowInformation,
+) -> Result<NotCurrentContext, GlWindowError> {
+    let raw_window_handle = window
+        .window_handle()
+        .map(|wh| Some(wh.as_raw()))
+        .map_err(GlWindowError::NoWindowHandle)?;
+    let gl_display = gl_config.display();
+
+    // A pinned version is what the renderer was written for, any other would only fail later.
+    if let Some((api, major, minor)) = window_info.gl_version {
+        let (name, context_api, profile) = pinned_context(api, major, minor);
+        let mut attributes = ContextAttributesBuilder::new().with_context_api(context_api);
+        if let Some(profile) = profile {
+            attributes = attributes.with_profile(profile);
+        }
+        let attributes = attributes.build(raw_window_handle);
+        return match unsafe { gl_display.create_context(gl_config, &attributes) } {
+            Ok(context) => {
+                log::info!("Created an {name} context");
+                Ok(context)
+            }
+            Err(err) => Err(GlWindowError::ContextCreation(vec![(name, err)])),
+        };
+    }
 
     // The context creation part.
     let context_attributes = ContextAttributesBuilder::new().build(raw_window_handle);
@@ -246,162 +651,1800 @@ fn create_gl_context(window: &window::Window, gl_config: &Config) -> NotCurrentC
         .with_context_api(ContextApi::OpenGl(Some(Version::new(2, 1))))
         .build(raw_window_handle);
 
-    // Reuse the uncurrented context from a suspended() call if it exists, otherwise
-    // this is the first time resumed() is called, where the context still
-    // has to be created.
-    let gl_display = gl_config.display();
+    let mut attempts = vec![("OpenGL", context_attributes)];
+    // The desktop bindings would be missing functions with an OpenGL ES context.
+    if cfg!(not(feature = "gl46")) {
+        attempts.push(("OpenGL ES", fallback_context_attributes));
+    }
+    if window_info.allow_legacy_fallback {
+        attempts.push(("OpenGL 2.1", legacy_context_attributes));
+    }
 
-    unsafe {
-        gl_display
-            .create_context(gl_config, &context_attributes)
-            .unwrap_or_else(|_| {
-                gl_display
-                    .create_context(gl_config, &fallback_context_attributes)
-                    .unwrap_or_else(|_| {
-                        gl_display
-                            .create_context(gl_config, &legacy_context_attributes)
-                            .expect("failed to create context")
-                    })
-            })
+    // Keep every error around, the last one alone rarely tells why nothing worked.
+    let mut failures = Vec::new();
+    for (name, attributes) in attempts {
+        match unsafe { gl_display.create_context(gl_config, &attributes) } {
+            Ok(context) => {
+                log::info!("Created an {name} context");
+                return Ok(context);
+            }
+            Err(err) => failures.push((name.to_string(), err)),
+        }
+    }
+    Err(GlWindowError::ContextCreation(failures))
+}
+
+fn is_input_event(event: &WindowEvent) -> bool {
+    matches!(
+        event,
+        WindowEvent::KeyboardInput { .. }
+            | WindowEvent::ModifiersChanged(_)
+            | WindowEvent::Ime(_)
+            | WindowEvent::CursorMoved { .. }
+            | WindowEvent::MouseWheel { .. }
+            | WindowEvent::MouseInput { .. }
+            | WindowEvent::Touch(_)
+            | WindowEvent::TouchpadPressure { .. }
+            | WindowEvent::AxisMotion { .. }
+            | WindowEvent::PinchGesture { .. }
+            | WindowEvent::PanGesture { .. }
+            | WindowEvent::RotationGesture { .. }
+            | WindowEvent::DoubleTapGesture { .. }
+    )
+}
+
+// Mouselook wants the cursor to stay in place, otherwise keeping it inside the window is
+// preferred. Platforms usually only support one of the two.
+fn grab_cursor(window: &window::Window, mouselook: bool) -> Result<(), ExternalError> {
+    let (preferred, fallback) = if mouselook {
+        (CursorGrabMode::Locked, CursorGrabMode::Confined)
+    } else {
+        (CursorGrabMode::Confined, CursorGrabMode::Locked)
+    };
+    window
+        .set_cursor_grab(preferred)
+        .or_else(|_| window.set_cursor_grab(fallback))
+}
+
+// What to ask for with `Window::set_gl_version`, named for the log and errors. Profiles only
+// exist from OpenGL 3.2 on, asking for one with an older version is an invalid combination.
+fn pinned_context(api: GlApi, major: u8, minor: u8) -> (String, ContextApi, Option<GlProfile>) {
+    let version = Some(Version::new(major, minor));
+    match api {
+        GlApi::OpenGl if (major, minor) >= (3, 2) => (
+            format!("OpenGL {major}.{minor} core"),
+            ContextApi::OpenGl(version),
+            Some(GlProfile::Core),
+        ),
+        GlApi::OpenGl => (
+            format!("OpenGL {major}.{minor}"),
+            ContextApi::OpenGl(version),
+            None,
+        ),
+        GlApi::Gles => (
+            format!("OpenGL ES {major}.{minor}"),
+            ContextApi::Gles(version),
+            None,
+        ),
+    }
+}
+
+// The monitor from `Window::set_monitor`, `None` to leave it to the system.
+fn chosen_monitor(
+    mut available: impl Iterator<Item = MonitorHandle>,
+    primary_monitor: Option<MonitorHandle>,
+    window_info: &WindowInformation,
+) -> Option<MonitorHandle> {
+    let index = window_info.monitor?;
+    available.nth(index).or_else(|| {
+        log::warn!("There is no monitor {index}, using the primary one");
+        primary_monitor
+    })
+}
+
+// Borderless on the chosen monitor or the one the window is on, unless a video mode was asked
+// for, which is looked up on the chosen or the primary monitor.
+fn fullscreen(
+    monitor: Option<MonitorHandle>,
+    primary_monitor: Option<MonitorHandle>,
+    window_info: &WindowInformation,
+) -> Option<window::Fullscreen> {
+    if !window_info.fullscreen {
+        return None;
     }
+    if let Some(requested) = window_info.exclusive_mode {
+        let mode = monitor
+            .clone()
+            .or(primary_monitor)
+            .and_then(|monitor| monitor::closest_video_mode(&monitor, requested));
+        match mode {
+            Some(mode) => return Some(window::Fullscreen::Exclusive(mode)),
+            None => log::warn!("No video mode for exclusive fullscreen, using borderless"),
+        }
+    }
+    Some(window::Fullscreen::Borderless(monitor))
+}
+
+// Relative to the corner of the chosen monitor, winit has no work area to keep clear of
+// panels. `None` to leave it to the system.
+fn window_position(
+    monitor: Option<&MonitorHandle>,
+    window_info: &WindowInformation,
+) -> Option<PhysicalPosition<i32>> {
+    let origin = monitor.map(|monitor| monitor.position());
+    if origin.is_none() && window_info.position.is_none() {
+        return None;
+    }
+    let origin = origin.unwrap_or_default();
+    let (x, y) = window_info.position.unwrap_or_default();
+    Some(PhysicalPosition::new(origin.x + x, origin.y + y))
 }
 
-fn window_attributes(window_info: &WindowInformation) -> WindowAttributes {
+fn window_attributes(
+    event_loop: &ActiveEventLoop,
+    window_info: &WindowInformation,
+) -> WindowAttributes {
+    let monitor = chosen_monitor(
+        event_loop.available_monitors(),
+        event_loop.primary_monitor(),
+        window_info,
+    );
     let mut attr = window::Window::default_attributes()
-        .with_fullscreen(if window_info.fullscreen {
-            Some(window::Fullscreen::Borderless(None))
-        } else {
-            None
-        })
+        .with_fullscreen(fullscreen(
+            monitor.clone(),
+            event_loop.primary_monitor(),
+            window_info,
+        ))
         .with_resizable(window_info.resizable)
+        .with_maximized(window_info.maximized)
+        // Shown in `resumed` once the cursor is set up.
+        .with_visible(!window_info.cursor_grabbed)
+        .with_enabled_buttons(window_info.enabled_buttons)
         .with_transparent(window_info.transparent)
+        .with_blur(window_info.blur)
         .with_title(&window_info.title)
-        .with_window_icon(window_info.icon.clone());
+        .with_window_icon(window_info.icon.as_ref().and_then(|(rgba, width, height)| {
+            Icon::from_rgba(rgba.clone(), *width as u32, *height as u32).ok()
+        }));
 
+    if let Some(position) = window_position(monitor.as_ref(), window_info) {
+        attr = attr.with_position(position);
+    }
     if let Some((x, y)) = window_info.size {
         attr = attr.with_inner_size(PhysicalSize::new(x as u32, y as u32));
     }
+    if let Some((width, height)) = window_info.min_size {
+        attr = attr.with_min_inner_size(PhysicalSize::new(width, height));
+    }
+    if let Some((width, height)) = window_info.max_size {
+        attr = attr.with_max_inner_size(PhysicalSize::new(width, height));
+    }
+
+    if window_info.blur {
+        attr = with_platform_blur(attr);
+    }
+
+    attr
+}
+
+// `with_blur` covers macOS and KWin on Wayland, Windows needs a system backdrop material
+// instead.
+#[cfg(windows)]
+fn with_platform_blur(attr: WindowAttributes) -> WindowAttributes {
+    use winit::platform::windows::{BackdropType, WindowAttributesExtWindows};
 
+    attr.with_system_backdrop(BackdropType::TransientWindow)
+}
+
+#[cfg(not(windows))]
+fn with_platform_blur(attr: WindowAttributes) -> WindowAttributes {
     attr
 }
 
+/// Which OpenGL flavor to create a context for, see [`Window::set_gl_version`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlApi {
+    /// Desktop OpenGL, with the core profile.
+    OpenGl,
+    Gles,
+}
+
+/// The platform API the OpenGL context was created with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GlBackend {
+    Egl,
+    Glx,
+    Wgl,
+    Cgl,
+}
+
+impl From<&glutin::display::Display> for GlBackend {
+    fn from(display: &glutin::display::Display) -> Self {
+        match display {
+            #[cfg(egl_backend)]
+            glutin::display::Display::Egl(_) => GlBackend::Egl,
+            #[cfg(glx_backend)]
+            glutin::display::Display::Glx(_) => GlBackend::Glx,
+            #[cfg(wgl_backend)]
+            glutin::display::Display::Wgl(_) => GlBackend::Wgl,
+            #[cfg(cgl_backend)]
+            glutin::display::Display::Cgl(_) => GlBackend::Cgl,
+        }
+    }
+}
+
 enum GlDisplayCreationState {
     /// The display was not build yet.
-    Builder(DisplayBuilder),
+    Uninit,
     /// The display was already created for the application.
     Init,
 }
 
+// Creates the renderer once there is a context.
+type RendererFactory<R> = Box<dyn FnOnce(gl::Gl, SurfaceSize) -> R>;
+
 struct App<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>> {
     template: ConfigTemplateBuilder,
+    // Taken when the renderer is created.
+    renderer_init: Option<RendererFactory<R>>,
     renderer: Option<R>,
     app_state: S,
     handler: H,
     window_info: WindowInformation,
+    // The present mode actually applied to the surface.
+    present_mode: Option<PresentMode>,
+    selected_format: Option<SelectedFormat>,
+    // Whether the window currently has keyboard focus.
+    focused: bool,
+    // Drawing is paused while minimized or fully covered. Tracked apart, since uncovering a
+    // minimized window still leaves nothing to draw into.
+    minimized: bool,
+    occluded: bool,
+    // The cursor should be grabbed, but the platform refused so far, usually because the
+    // window wasn't focused yet.
+    grab_pending: bool,
+    cursor_confinement: Option<CursorConfinement>,
+    last_input: Instant,
+    // When the next frame is due while the frame rate is limited.
+    next_frame: Instant,
+    // The clock reading of the last frame, see `App::clock`.
+    last_frame: Option<Duration>,
+    frame_count: u64,
+    started: Instant,
+    // Time the clock was paused for, on top of the running pause if any.
+    paused_total: Duration,
+    paused_at: Option<Instant>,
+    // Time not yet simulated by fixed updates.
+    accumulator: Duration,
+    // The first frame waits for the platform to report the real window size, or until the
+    // deadline for platforms that don't.
+    seen_resize: bool,
+    first_frame_deadline: Instant,
+    // Draw on `RedrawRequested` instead of right away in `about_to_wait`, on Wayland.
+    frame_callbacks: bool,
+    power: Option<PowerMonitor>,
+    gestures: GestureRecognizer,
+    // The input for the next frame, reset after drawing it.
+    input: InputState,
+    touch: TouchState,
     // NOTE: `GlState` carries the `Window`, thus it should be dropped after everything else.
     gl_state: Option<GlState>,
     gl_context: Option<PossiblyCurrentContext>,
     gl_display: GlDisplayCreationState,
-    exit_state: Result<(), Box<dyn Error>>,
+    // Our own copy of the functions the renderer got, for the work done around its draw calls.
+    gl: Option<gl::Gl>,
+    offscreen: Option<Offscreen>,
+    taskbar: Option<TaskbarProgress>,
+    #[cfg(feature = "global-hotkey")]
+    hotkeys: Option<hotkey::HotkeyListener>,
+    // Saved after drawing the next frame.
+    #[cfg(feature = "screenshot")]
+    screenshot: Option<PathBuf>,
+    #[cfg(feature = "screenshot")]
+    recorder: Option<recording::Recorder>,
+    benchmark: Option<Benchmark>,
+    // The context is being recreated because the old one was lost.
+    context_lost: bool,
+    // For the `RedrawRequester`s handed out to the handler.
+    proxy: EventLoopProxy<LoopEvent>,
+    exit_state: Result<(), GlWindowError>,
 }
 
 impl<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>> App<S, H, R> {
     fn new(
         template: ConfigTemplateBuilder,
         window_info: WindowInformation,
-        display_builder: DisplayBuilder,
+        renderer_init: RendererFactory<R>,
         app_state: S,
         handler: H,
+        proxy: EventLoopProxy<LoopEvent>,
     ) -> Self {
         Self {
             template,
+            renderer_init: Some(renderer_init),
             app_state,
             handler,
+            present_mode: None,
+            selected_format: None,
+            // Assume focus until told otherwise, not every platform reports the initial state.
+            focused: true,
+            minimized: false,
+            occluded: false,
+            grab_pending: false,
+            cursor_confinement: None,
+            last_input: Instant::now(),
+            next_frame: Instant::now(),
+            last_frame: None,
+            frame_count: 0,
+            started: Instant::now(),
+            paused_total: Duration::ZERO,
+            paused_at: None,
+            accumulator: Duration::ZERO,
+            seen_resize: false,
+            first_frame_deadline: Instant::now(),
+            frame_callbacks: false,
+            power: window_info.power_aware.then(PowerMonitor::new),
+            gestures: GestureRecognizer::new(window_info.gesture_thresholds),
+            input: InputState::new(),
+            touch: TouchState::new(),
             window_info,
             renderer: None,
-            gl_display: GlDisplayCreationState::Builder(display_builder),
+            gl_display: GlDisplayCreationState::Uninit,
             gl_context: None,
             gl_state: None,
+            gl: None,
+            offscreen: None,
+            taskbar: None,
+            #[cfg(feature = "global-hotkey")]
+            hotkeys: None,
+            #[cfg(feature = "screenshot")]
+            screenshot: None,
+            #[cfg(feature = "screenshot")]
+            recorder: None,
+            benchmark: None,
+            context_lost: false,
+            proxy,
             exit_state: Ok(()),
         }
     }
-}
-
-struct GlState {
-    gl_surface: Surface<WindowSurface>,
-    // NOTE: Window should be dropped after all resources created using its
-    // raw-window-handle.
-    window: window::Window,
-}
 
-// Find the config with the maximum number of samples, so our triangle will be
-// smooth.
-pub fn gl_config_picker(configs: Box<dyn Iterator<Item = Config> + '_>) -> Config {
-    configs
-        .reduce(|accum, config| {
-            let transparency_check = config.supports_transparency().unwrap_or(false)
-                & !accum.supports_transparency().unwrap_or(false);
+    // Everything after the window, config and surface exist, shared by `resumed` and
+    // `recreate_surface`.
+    fn finish_resume(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        window: window::Window,
+        gl_config: Config,
+        gl_surface: Surface<WindowSurface>,
+    ) {
+        // Set up the cursor before the window shows up, so it never flickers by ungrabbed.
+        // Some platforms only grab for a focused window though, then it's retried on focus.
+        window.set_cursor_visible(self.window_info.cursor_visible);
+        if self.window_info.cursor_grabbed {
+            self.grab_pending = grab_cursor(&window, self.window_info.mouselook).is_err();
+        }
+        window.set_visible(true);
+        if self.window_info.sticky {
+            sticky::make_sticky(&window);
+        }
 
-            if transparency_check || config.num_samples() > accum.num_samples() {
-                config
-            } else {
-                accum
+        // Once the window is shown, and only the first one. A recreated window shouldn't move
+        // the cursor around.
+        if let Some((x, y)) = self.window_info.initial_cursor_position.take() {
+            if let Err(err) = window.set_cursor_position(PhysicalPosition::new(x, y)) {
+                log::warn!("Failed to set the cursor position: {err}");
             }
-        })
-        .unwrap()
-}
-
-pub trait AppRenderer {
-    type AppState;
+        }
 
-    fn new(gl: gl::Gl) -> Self;
-    fn draw(&self, app_state: &mut Self::AppState);
-    fn resize(&mut self, _width: i32, _height: i32) {}
-}
+        // The context needs to be current for the Renderer to set up shaders and
+        // buffers. It also performs function loading, which needs a current context on
+        // WGL.
+        let gl_context = self.gl_context.as_ref().unwrap();
+        if let Err(err) = gl_context.make_current(&gl_surface) {
+            self.fail(event_loop, GlWindowError::Context(err));
+            return;
+        }
 
-pub enum AppControl {
-    Continue,
-    Exit,
-}
+        // A new context, either the first one or a replacement from `recreate_surface`.
+        if self.gl.is_none() {
+            let gl = gl::Gl::load_with(|symbol| {
+                let symbol = CString::new(symbol).unwrap();
+                gl_config
+                    .display()
+                    .get_proc_address(symbol.as_c_str())
+                    .cast()
+            });
 
-pub trait AppEventHandler {
-    type AppState;
-    fn handle_event(
-        &mut self,
-        app_state: &mut Self::AppState,
-        event: WindowEvent,
-    ) -> Result<AppControl, Box<dyn Error>>;
-}
+            // With an internal resolution that's the only size the renderer ever sees.
+            let initial_size = match self.window_info.internal_resolution {
+                Some(size) => SurfaceSize::new(size.into(), 1.0),
+                None => SurfaceSize::new(window.inner_size(), window.scale_factor()),
+            };
 
-impl<S> AppEventHandler for fn(&mut S, WindowEvent) -> Result<AppControl, Box<dyn Error>> {
-    type AppState = S;
-    fn handle_event(
-        &mut self,
-        app_state: &mut Self::AppState,
-        event: WindowEvent,
-    ) -> Result<AppControl, Box<dyn Error>> {
-        self(app_state, event)
-    }
-}
+            // Before the renderer, which shouldn't be created for a context it can't be shown
+            // with.
+            if let Some(size) = self.window_info.internal_resolution {
+                let pixel_perfect = self.window_info.pixel_perfect;
+                match unsafe { Offscreen::new(&gl, size, pixel_perfect) } {
+                    Ok(offscreen) => self.offscreen = Some(offscreen),
+                    Err(err) => {
+                        self.fail(event_loop, err);
+                        return;
+                    }
+                }
+            }
 
-pub type HandleFn<S> = for<'a> fn(
-    &'a mut S,
-    WindowEvent,
-) -> Result<AppControl, Box<(dyn std::error::Error + 'static)>>;
+            match self.renderer.as_mut() {
+                Some(renderer) => {
+                    let lost = std::mem::take(&mut self.context_lost);
+                    context_replaced(renderer, gl.clone(), lost, initial_size);
+                }
+                None => {
+                    let factory = self.renderer_init.take().expect("renderer created twice");
+                    let mut renderer = factory(gl.clone(), initial_size);
+                    let (width, height) = initial_size.physical.into();
+                    renderer.init(width, height);
+                    if self.window_info.internal_resolution.is_some() {
+                        renderer.surface_resized(initial_size);
+                    }
+                    self.renderer = Some(renderer);
+                }
+            }
+
+            self.gl = Some(gl);
+        }
+
+        // Cover the whole surface, or the offscreen framebuffer, so renderers that never call
+        // `glViewport` still get a correct first frame.
+        if let Some(gl) = self.gl.as_ref() {
+            let (width, height) = self
+                .window_info
+                .internal_resolution
+                .unwrap_or_else(|| window.inner_size().into());
+            unsafe { gl.Viewport(0, 0, width as i32, height as i32) };
+        }
+
+        // Try setting vsync.
+        self.present_mode = present::apply(
+            &gl_surface,
+            gl_context,
+            self.window_info.present_mode,
+            self.window_info.vblanks,
+        );
+        let requested_samples = self.window_info.format.samples;
+        let samples = gl_config.num_samples();
+        if let Some(requested) = requested_samples.filter(|&requested| requested != samples) {
+            log::warn!("Asked for {requested} samples per pixel, got {samples}");
+        }
+        // Without a compositor the alpha channel ends up black instead of see-through.
+        let transparent = self.window_info.transparent
+            && gl_config.supports_transparency().unwrap_or(false)
+            && compositor::is_compositing(&window);
+        let (red_bits, green_bits, blue_bits) = match gl_config.color_buffer_type() {
+            Some(ColorBufferType::Rgb {
+                r_size,
+                g_size,
+                b_size,
+            }) => (r_size, g_size, b_size),
+            _ => (0, 0, 0),
+        };
+        self.selected_format = Some(SelectedFormat {
+            swap_interval_applied: self.present_mode.is_some(),
+            samples,
+            requested_samples,
+            transparent,
+            red_bits,
+            green_bits,
+            blue_bits,
+            alpha_bits: gl_config.alpha_size(),
+        });
+
+        assert!(self
+            .gl_state
+            .replace(GlState { gl_surface, window })
+            .is_none());
+
+        // winit delivers redraw requests on Wayland as the compositor's frame callbacks arrive,
+        // drawing any faster than that only queues up frames.
+        #[cfg(wayland_platform)]
+        {
+            use winit::platform::wayland::ActiveEventLoopExtWayland;
+            self.frame_callbacks = event_loop.is_wayland();
+        }
+
+        let window = &self.gl_state.as_ref().unwrap().window;
+        // Even without anything else asking for one, there should be a first frame.
+        window.request_redraw();
+        if self.frame_count == 0 {
+            self.first_frame_deadline = Instant::now() + FIRST_FRAME_TIMEOUT;
+        }
+        let handles = window
+            .window_handle()
+            .ok()
+            .zip(window.display_handle().ok());
+        if let Some((window_handle, display_handle)) = handles {
+            let (window_handle, display_handle) = (window_handle.as_raw(), display_handle.as_raw());
+            match self
+                .handler
+                .on_window_created(&mut self.app_state, window_handle, display_handle)
+            {
+                Ok(control) => self.apply_control(event_loop, control),
+                Err(e) => {
+                    self.fail(event_loop, GlWindowError::Handler(e));
+                }
+            }
+        }
+    }
+
+    // Replace the config, and with it the window, surface and context, for `format`.
+    fn recreate_surface(&mut self, event_loop: &ActiveEventLoop, format: FormatOptions) {
+        if self.gl_state.is_none() {
+            self.fail(event_loop, GlWindowError::NoSurface);
+            return;
+        }
+        self.window_info.format = format;
+
+        // The recording's buffers belong to the old context.
+        #[cfg(feature = "screenshot")]
+        self.stop_recording(event_loop, None);
+
+        // Free our own objects while the old context is still current, the renderer's go
+        // with the context.
+        let gl = self.gl.take().unwrap();
+        if let Some(offscreen) = self.offscreen.take() {
+            unsafe { offscreen.delete(&gl) };
+        }
+        let gl_context = self.gl_context.take().unwrap();
+        let display = gl_context.display();
+        self.gl_state = None;
+        drop(gl_context.make_not_current());
+
+        let created =
+            create_with_new_config(event_loop, &display, &self.template, &self.window_info)
+                .and_then(|(window, gl_config, gl_surface)| {
+                    let context = create_gl_context(&window, &gl_config, &self.window_info)?;
+                    Ok((window, gl_config, gl_surface, context))
+                });
+
+        match created {
+            Ok((window, gl_config, gl_surface, context)) => {
+                self.gl_context = Some(context.treat_as_possibly_current());
+                self.finish_resume(event_loop, window, gl_config, gl_surface);
+            }
+            Err(err) => {
+                self.fail(event_loop, err);
+            }
+        }
+    }
+
+    fn render_frame(&mut self, event_loop: &ActiveEventLoop) {
+        let (Some(GlState { gl_surface, window }), Some(format)) =
+            (self.gl_state.as_ref(), self.selected_format)
+        else {
+            return;
+        };
+
+        let uptime = self.clock(Instant::now());
+        let dt = self
+            .last_frame
+            .map_or(Duration::ZERO, |last_frame| uptime - last_frame);
+        self.last_frame = Some(uptime);
+
+        // The time left over from the fixed updates carries over to the next frame.
+        let alpha = match self.window_info.fixed_timestep {
+            Some(step) => {
+                let renderer: &mut R = self.renderer.as_mut().unwrap();
+                self.accumulator += dt;
+                let mut steps = 0;
+                while self.accumulator >= step && steps < MAX_FIXED_STEPS {
+                    renderer.update(&mut self.app_state, step);
+                    self.accumulator -= step;
+                    steps += 1;
+                }
+                if self.accumulator >= step {
+                    // Give up on catching up, the simulation runs slower instead.
+                    let behind = self.accumulator.as_secs_f64() % step.as_secs_f64();
+                    self.accumulator = Duration::from_secs_f64(behind);
+                }
+                self.accumulator.as_secs_f64() / step.as_secs_f64()
+            }
+            None => 1.,
+        };
+
+        let gl_context = self.gl_context.as_ref().unwrap();
+        let renderer = self.renderer.as_ref().unwrap();
+        let offscreen = self.gl.as_ref().zip(self.offscreen.as_ref());
+        if let Some((gl, offscreen)) = offscreen {
+            unsafe { offscreen.bind(gl) };
+        }
+
+        if let Some((gl, [r, g, b, a])) = self.gl.as_ref().zip(self.window_info.clear_color) {
+            unsafe {
+                // Leave the renderer's own clear color alone.
+                let mut clear_color = [0.; 4];
+                gl.GetFloatv(gl::COLOR_CLEAR_VALUE, clear_color.as_mut_ptr());
+                if self.window_info.transparent {
+                    gl_clear_transparent(gl, r, g, b, a);
+                } else {
+                    gl.ClearColor(r, g, b, a);
+                    gl.Clear(gl::COLOR_BUFFER_BIT);
+                }
+                let [r, g, b, a] = clear_color;
+                gl.ClearColor(r, g, b, a);
+            }
+        }
+
+        if let Some(benchmark) = self.benchmark.as_mut() {
+            benchmark.frame_started();
+        }
+
+        let frame_count = self.frame_count;
+        self.frame_count += 1;
+
+        let surface_size = self
+            .window_info
+            .internal_resolution
+            .unwrap_or_else(|| window.inner_size().into());
+        let window_size: (u32, u32) = window.inner_size().into();
+        let target_rect = match offscreen {
+            Some((_, offscreen)) => offscreen.target_rect(window_size),
+            None => (0, 0, window_size.0 as i32, window_size.1 as i32),
+        };
+        let control = renderer.draw_with_ctx(FrameCtx {
+            gl: self.gl.as_ref().unwrap(),
+            app_state: &mut self.app_state,
+            window,
+            surface_size,
+            dt,
+            frame_count,
+            uptime,
+            alpha,
+            input: &self.input,
+            touch: &self.touch,
+            format,
+            transparent: self.window_info.transparent,
+            target_rect,
+        });
+        self.input.end_frame();
+
+        if let Some((gl, offscreen)) = offscreen {
+            let size = window.inner_size();
+            unsafe { offscreen.present(gl, (size.width, size.height)) };
+        }
+
+        // Read back before swapping, the back buffer is undefined afterwards.
+        #[cfg(feature = "screenshot")]
+        let screenshot = self.screenshot.take().map(|path| {
+            let size = window.inner_size();
+            let gl = self.gl.as_ref().unwrap();
+            let result = unsafe { screenshot::save(gl, (size.width, size.height), &path) };
+            (path, result)
+        });
+        #[cfg(feature = "screenshot")]
+        let recording_failed = self.recorder.as_mut().and_then(|recorder| {
+            let size = window.inner_size();
+            let gl = self.gl.as_ref().unwrap();
+            unsafe { recorder.capture(gl, (size.width, size.height)) }.err()
+        });
+
+        // Lets winit throttle the next redraw request to the compositor.
+        window.pre_present_notify();
+        match presented(gl_surface.swap_buffers(gl_context), &mut self.context_lost) {
+            Ok(Presented::Shown) => (),
+            Ok(Presented::ContextLost) => {
+                self.recreate_surface(event_loop, self.window_info.format);
+                return;
+            }
+            Err(err) => {
+                self.fail(event_loop, err);
+                return;
+            }
+        }
+
+        if let Some(benchmark) = self.benchmark.as_mut() {
+            if benchmark.frame_swapped() {
+                event_loop.exit();
+            }
+        }
+
+        #[cfg(feature = "screenshot")]
+        if let Some((path, result)) = screenshot {
+            self.screenshot_saved(event_loop, &path, result);
+        }
+        #[cfg(feature = "screenshot")]
+        if let Some(e) = recording_failed {
+            self.stop_recording(event_loop, Some(e));
+        }
+
+        match control {
+            Ok(control) => self.apply_control(event_loop, control),
+            Err(e) => self.fail(event_loop, GlWindowError::Handler(e)),
+        }
+    }
+
+    // Report the end of the recording, with `error` if capturing a frame failed. The worker
+    // knows best what went wrong, so its error takes precedence.
+    #[cfg(feature = "screenshot")]
+    fn stop_recording(&mut self, event_loop: &ActiveEventLoop, error: Option<Box<dyn Error>>) {
+        let Some(recorder) = self.recorder.take() else {
+            return;
+        };
+        let dir = recorder.dir().to_path_buf();
+        let gl = self.gl.as_ref().unwrap();
+        let result = match (unsafe { recorder.stop(gl) }, error) {
+            (Err(e), _) | (Ok(()), Some(e)) => Err(e),
+            (Ok(()), None) => Ok(()),
+        };
+        self.recording_stopped(event_loop, &dir, result);
+    }
+
+    #[cfg(feature = "screenshot")]
+    fn recording_stopped(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        dir: &Path,
+        result: Result<(), Box<dyn Error>>,
+    ) {
+        match self
+            .handler
+            .handle_recording_stopped(&mut self.app_state, dir, result)
+        {
+            Ok(control) => self.apply_control(event_loop, control),
+            Err(e) => {
+                self.fail(event_loop, GlWindowError::Handler(e));
+            }
+        }
+    }
+
+    #[cfg(feature = "screenshot")]
+    fn screenshot_saved(
+        &mut self,
+        event_loop: &ActiveEventLoop,
+        path: &Path,
+        result: Result<(), Box<dyn Error>>,
+    ) {
+        match self
+            .handler
+            .handle_screenshot_saved(&mut self.app_state, path, result)
+        {
+            Ok(control) => self.apply_control(event_loop, control),
+            Err(e) => {
+                self.fail(event_loop, GlWindowError::Handler(e));
+            }
+        }
+    }
+
+    // Keep the cursor in the rectangle of `AppControl::ConfineCursorToRect`, if any.
+    fn confine_cursor(
+        &mut self,
+        confine: impl FnOnce(&mut CursorConfinement, &window::Window) -> Result<(), ExternalError>,
+    ) {
+        let (Some(confinement), Some(GlState { window, .. })) =
+            (self.cursor_confinement.as_mut(), self.gl_state.as_ref())
+        else {
+            return;
+        };
+        // Other windows get to use the cursor.
+        if !self.focused {
+            return;
+        }
+        if let Err(err) = confine(confinement, window) {
+            // Wayland for one doesn't allow warping, confining to the whole window is the
+            // closest there is.
+            log::warn!("Failed to move the cursor, confining it to the window instead: {err}");
+            self.cursor_confinement = None;
+            if let Err(err) = window.set_cursor_grab(CursorGrabMode::Confined) {
+                log::warn!("Failed to grab the cursor: {err}");
+            }
+        }
+    }
+
+    fn resize_surface(&mut self, size: PhysicalSize<u32>) {
+        // Some platforms like EGL require resizing GL surface to update the size
+        // Notable platforms here are Wayland and macOS, other don't require it
+        // and the function is no-op, but it's wise to resize it for portability
+        // reasons.
+        if let Some(GlState { gl_surface, window }) = self.gl_state.as_ref() {
+            let gl_context = self.gl_context.as_ref().unwrap();
+            gl_surface.resize(
+                gl_context,
+                NonZeroU32::new(size.width).unwrap(),
+                NonZeroU32::new(size.height).unwrap(),
+            );
+
+            window.request_redraw();
+
+            // With an internal resolution the renderer's size never changes, only the
+            // area it's scaled into.
+            if self.offscreen.is_none() {
+                let renderer: &mut R = self.renderer.as_mut().unwrap();
+                renderer.surface_resized(SurfaceSize::new(size, window.scale_factor()));
+            }
+        }
+    }
+
+    // Ask for a size with the locked aspect ratio, giving the size to use until it arrives. The
+    // side that changed leads, so dragging either edge works.
+    fn keep_aspect_ratio(&self, size: PhysicalSize<u32>) -> PhysicalSize<u32> {
+        let (Some((ratio_width, ratio_height)), Some(GlState { gl_surface, window })) =
+            (self.window_info.aspect_ratio, self.gl_state.as_ref())
+        else {
+            return size;
+        };
+        if self.window_info.fullscreen || window.is_maximized() {
+            return size;
+        }
+
+        let height_changed =
+            gl_surface.width() == Some(size.width) && gl_surface.height() != Some(size.height);
+        let corrected = if height_changed {
+            let width = u64::from(size.height) * u64::from(ratio_width) / u64::from(ratio_height);
+            PhysicalSize::new((width as u32).max(1), size.height)
+        } else {
+            let height = u64::from(size.width) * u64::from(ratio_height) / u64::from(ratio_width);
+            PhysicalSize::new(size.width, (height as u32).max(1))
+        };
+
+        // Off by rounding is close enough, correcting it would only bring another `Resized`
+        // that's off again.
+        if corrected.width.abs_diff(size.width) <= 1 && corrected.height.abs_diff(size.height) <= 1
+        {
+            return size;
+        }
+        window.request_inner_size(corrected).unwrap_or(size)
+    }
+
+    fn waiting_for_size(&self) -> bool {
+        self.frame_count == 0 && !self.seen_resize && Instant::now() < self.first_frame_deadline
+    }
+
+    // Whether frames are drawn in response to `RedrawRequested`, or right away when the loop
+    // runs out of events.
+    fn draws_on_redraw(&self) -> bool {
+        // With frame callbacks the frame is drawn once the compositor asks for it.
+        self.frame_callbacks
+            || self.window_info.loop_mode != LoopMode::Poll
+            || !self.window_info.auto_request_redraw
+    }
+
+    fn set_fullscreen(&mut self, fullscreen: bool) {
+        // Kept for when the window gets recreated. The surface follows through `Resized`.
+        self.window_info.fullscreen = fullscreen;
+        if let Some(GlState { window, .. }) = self.gl_state.as_ref() {
+            let monitor = chosen_monitor(
+                window.available_monitors(),
+                window.primary_monitor(),
+                &self.window_info,
+            );
+            window.set_fullscreen(self::fullscreen(
+                monitor,
+                window.primary_monitor(),
+                &self.window_info,
+            ));
+        }
+    }
+
+    fn set_monitor(&mut self, index: usize) {
+        // Like `set_fullscreen`, kept for a recreated window.
+        self.window_info.monitor = Some(index);
+        if let Some(GlState { window, .. }) = self.gl_state.as_ref() {
+            let monitor = chosen_monitor(
+                window.available_monitors(),
+                window.primary_monitor(),
+                &self.window_info,
+            );
+            if self.window_info.fullscreen {
+                window.set_fullscreen(self::fullscreen(
+                    monitor,
+                    window.primary_monitor(),
+                    &self.window_info,
+                ));
+            } else if let Some(position) = window_position(monitor.as_ref(), &self.window_info) {
+                window.set_outer_position(position);
+            }
+        }
+    }
+
+    fn hidden(&self) -> bool {
+        self.minimized || self.occluded
+    }
+
+    fn set_minimized(&mut self, minimized: bool) {
+        let was_hidden = self.hidden();
+        self.minimized = minimized;
+        self.visibility_changed(was_hidden);
+    }
+
+    fn set_occluded(&mut self, occluded: bool) {
+        let was_hidden = self.hidden();
+        self.occluded = occluded;
+        self.visibility_changed(was_hidden);
+    }
+
+    fn visibility_changed(&mut self, was_hidden: bool) {
+        let hidden = self.hidden();
+        if hidden != was_hidden {
+            if let Some(renderer) = self.renderer.as_mut() {
+                renderer.visibility_changed(!hidden);
+            }
+        }
+    }
+
+    fn dispatch_event(&mut self, event_loop: &ActiveEventLoop, event: WindowEvent) {
+        let window = WindowControls {
+            window: self.gl_state.as_ref().map(|state| &state.window),
+            backend: self
+                .gl_context
+                .as_ref()
+                .map(|context| GlBackend::from(&context.display())),
+            present_mode: self.present_mode,
+            selected_format: self.selected_format,
+            gl_context: self.gl_context.as_ref(),
+            actions: &self.window_info.actions,
+            proxy: &self.proxy,
+        };
+        match self
+            .handler
+            .handle_window_event(&mut self.app_state, event, &window)
+        {
+            Ok(control) => self.apply_control(event_loop, control),
+            Err(e) => {
+                self.fail(event_loop, GlWindowError::Handler(e));
+            }
+        }
+    }
+
+    fn dispatch_gesture(&mut self, event_loop: &ActiveEventLoop, gesture: Gesture) {
+        let result = match gesture {
+            Gesture::Zoom(factor) => self.handler.handle_pinch_zoom(&mut self.app_state, factor),
+            Gesture::Pan(delta) => self.handler.handle_pan(&mut self.app_state, delta),
+        };
+        match result {
+            Ok(control) => self.apply_control(event_loop, control),
+            Err(e) => {
+                self.fail(event_loop, GlWindowError::Handler(e));
+            }
+        }
+    }
+
+    // Time since the app started, without the time the clock was paused.
+    fn clock(&self, now: Instant) -> Duration {
+        let paused = self.paused_total + self.paused_at.map_or(Duration::ZERO, |at| now - at);
+        (now - self.started).saturating_sub(paused)
+    }
+
+    // Handle an error according to the error policy.
+    fn fail(&mut self, event_loop: &ActiveEventLoop, err: GlWindowError) {
+        match route_error(&mut self.window_info.error_policy, err) {
+            Ok(control) => self.apply_control(event_loop, control),
+            Err(err) => {
+                self.exit_state = Err(err);
+                event_loop.exit();
+            }
+        }
+    }
+
+    fn apply_control(&mut self, event_loop: &ActiveEventLoop, control: AppControl) {
+        match control {
+            AppControl::Continue => (),
+            AppControl::Exit => event_loop.exit(),
+            AppControl::Redraw => {
+                if let Some(GlState { window, .. }) = self.gl_state.as_ref() {
+                    window.request_redraw();
+                }
+            }
+            AppControl::SetFullscreen(fullscreen) => self.set_fullscreen(fullscreen),
+            AppControl::ToggleFullscreen => self.set_fullscreen(!self.window_info.fullscreen),
+            AppControl::SetMonitor(index) => self.set_monitor(index),
+            AppControl::PauseClock => {
+                self.paused_at.get_or_insert_with(Instant::now);
+            }
+            AppControl::ResumeClock => {
+                if let Some(paused_at) = self.paused_at.take() {
+                    self.paused_total += paused_at.elapsed();
+                }
+            }
+            AppControl::SetEnabledButtons(buttons) => {
+                // Keep the buttons around for when the window gets recreated.
+                self.window_info.enabled_buttons = buttons;
+                if let Some(GlState { window, .. }) = self.gl_state.as_ref() {
+                    window.set_enabled_buttons(buttons);
+                }
+            }
+            AppControl::RecreateSurface(format) => self.recreate_surface(event_loop, format),
+            AppControl::SetPresentMode(mode) => {
+                // Kept for when the surface gets recreated.
+                self.window_info.present_mode = mode;
+                let (Some(GlState { gl_surface, .. }), Some(gl_context)) =
+                    (self.gl_state.as_ref(), self.gl_context.as_ref())
+                else {
+                    return;
+                };
+                let vblanks = self.window_info.vblanks;
+                self.present_mode = present::apply(gl_surface, gl_context, mode, vblanks);
+                if let Some(format) = self.selected_format.as_mut() {
+                    format.swap_interval_applied = self.present_mode.is_some();
+                }
+            }
+            AppControl::SetMinSize(size) => {
+                // Kept for when the window gets recreated, like the buttons.
+                self.window_info.min_size = size;
+                if let Some(GlState { window, .. }) = self.gl_state.as_ref() {
+                    window.set_min_inner_size(size.map(|(w, h)| PhysicalSize::new(w, h)));
+                }
+            }
+            AppControl::SetMaxSize(size) => {
+                self.window_info.max_size = size;
+                if let Some(GlState { window, .. }) = self.gl_state.as_ref() {
+                    window.set_max_inner_size(size.map(|(w, h)| PhysicalSize::new(w, h)));
+                }
+            }
+            AppControl::ConfineCursorToRect(rect) => {
+                self.cursor_confinement = rect.map(CursorConfinement::new);
+                // Undo the fallback grab, unless the window wants one anyway.
+                if rect.is_none() && !self.window_info.cursor_grabbed {
+                    if let Some(GlState { window, .. }) = self.gl_state.as_ref() {
+                        let _ = window.set_cursor_grab(CursorGrabMode::None);
+                    }
+                }
+            }
+            AppControl::RequestInnerSize((width, height)) => {
+                let Some(GlState { window, .. }) = self.gl_state.as_ref() else {
+                    return;
+                };
+                // Most platforms resize later and report it with `Resized`, some right away.
+                let size = window.request_inner_size(PhysicalSize::new(width, height));
+                if let Some(size) = size.filter(|size| size.width != 0 && size.height != 0) {
+                    self.resize_surface(size);
+                }
+            }
+            AppControl::SetTaskbarProgress(progress) => {
+                if let Some(GlState { window, .. }) = self.gl_state.as_ref() {
+                    if self.taskbar.is_none() {
+                        self.taskbar = TaskbarProgress::new();
+                    }
+                    if let Some(taskbar) = self.taskbar.as_ref() {
+                        taskbar.set(window, progress);
+                    }
+                }
+            }
+            #[cfg(feature = "screenshot")]
+            AppControl::SaveScreenshot(path) => match self.gl_state.as_ref() {
+                Some(GlState { window, .. }) => {
+                    self.screenshot = Some(path);
+                    window.request_redraw();
+                }
+                None => {
+                    self.screenshot_saved(event_loop, &path, Err(GlWindowError::NoSurface.into()))
+                }
+            },
+            #[cfg(feature = "screenshot")]
+            AppControl::StartRecording(dir, every_n) => {
+                self.stop_recording(event_loop, None);
+                let started = match (self.gl.as_ref(), self.gl_state.as_ref()) {
+                    (Some(gl), Some(_)) => unsafe {
+                        recording::Recorder::start(gl, dir.clone(), every_n)
+                    },
+                    _ => Err(GlWindowError::NoSurface.into()),
+                };
+                match started {
+                    Ok(recorder) => self.recorder = Some(recorder),
+                    Err(e) => self.recording_stopped(event_loop, &dir, Err(e)),
+                }
+            }
+            #[cfg(feature = "screenshot")]
+            AppControl::StopRecording => self.stop_recording(event_loop, None),
+        }
+    }
+}
+
+struct GlState {
+    gl_surface: Surface<WindowSurface>,
+    // NOTE: Window should be dropped after all resources created using its
+    // raw-window-handle.
+    window: window::Window,
+}
+
+/// Clear the color buffer to a color with straight alpha, as the compositor of a transparent
+/// window expects it: premultiplied, `(r * a, g * a, b * a, a)`.
+///
+/// Clearing a transparent window to `(1.0, 0.0, 0.0, 0.5)` directly would composite as if the
+/// red was fully opaque on top of an only half transparent background, brighter than intended.
+///
+/// # Safety
+///
+/// The context the `gl` functions were loaded for must be current.
+pub unsafe fn gl_clear_transparent(gl: &gl::Gl, r: f32, g: f32, b: f32, a: f32) {
+    gl.ClearColor(r * a, g * a, b * a, a);
+    gl.Clear(gl::COLOR_BUFFER_BIT);
+}
+
+// Pick with the app's own picker if there is one, otherwise by the requested format first,
+// then like `gl_config_picker`.
+fn pick_config(
+    configs: Box<dyn Iterator<Item = Config> + '_>,
+    window_info: &WindowInformation,
+) -> Option<Config> {
+    if let Some(picker) = &window_info.config_picker {
+        return picker(configs);
+    }
+    let format = &window_info.format;
+    let (transparent, require_transparency) =
+        (window_info.transparent, window_info.require_transparency);
+    let mut configs: Vec<Config> = configs.collect();
+    // Without any transparent config the pick doesn't matter, it's an error anyway.
+    if transparent
+        && require_transparency
+        && configs
+            .iter()
+            .any(|config| config.supports_transparency().unwrap_or(false))
+    {
+        configs.retain(|config| config.supports_transparency().unwrap_or(false));
+    }
+    // `gl_config_picker` prefers transparency, but an opaque window with an alpha channel can
+    // still end up see-through, e.g. with an ARGB visual on X11.
+    if !transparent
+        && configs
+            .iter()
+            .any(|config| !config.supports_transparency().unwrap_or(false))
+    {
+        configs.retain(|config| !config.supports_transparency().unwrap_or(false));
+    }
+    if let Some(srgb) = format.srgb {
+        // Only a preference, a surface without sRGB beats no surface at all.
+        if configs.iter().any(|config| config.srgb_capable() == srgb) {
+            configs.retain(|config| config.srgb_capable() == srgb);
+        }
+    }
+    match format.samples {
+        Some(samples) => {
+            // A single sample is no multisampling, which configs report as `0`.
+            let samples = if samples <= 1 { 0 } else { samples };
+            // The requested count, or the closest lower one, more only when there is no lower
+            // one. Prefer transparency among equally close ones.
+            configs.into_iter().min_by_key(|config| {
+                (
+                    config.num_samples() > samples,
+                    config.num_samples().abs_diff(samples),
+                    !config.supports_transparency().unwrap_or(false),
+                )
+            })
+        }
+        None => gl_config_picker(Box::new(configs.into_iter())),
+    }
+}
+
+// Find the config with the maximum number of samples, so our triangle will be
+// smooth. `None` when there are no configs at all.
+pub fn gl_config_picker(configs: Box<dyn Iterator<Item = Config> + '_>) -> Option<Config> {
+    configs.reduce(|accum, config| {
+        let transparency_check = config.supports_transparency().unwrap_or(false)
+            & !accum.supports_transparency().unwrap_or(false);
+
+        if transparency_check || config.num_samples() > accum.num_samples() {
+            config
+        } else {
+            accum
+        }
+    })
+}
+
+// Presenting failed because the context or its surface are gone, after a driver reset or a GPU
+// switch, rather than for something a new context wouldn't fix.
+fn is_context_loss(err: &glutin::error::Error) -> bool {
+    matches!(
+        err.error_kind(),
+        ErrorKind::ContextLost | ErrorKind::BadSurface
+    )
+}
+
+enum Presented {
+    Shown,
+    // The surface and context have to be recreated before the next frame.
+    ContextLost,
+}
+
+// After a driver reset or a GPU switch everything starts over on a new context, which the
+// renderer is told about through `context_lost`. Any other failure ends the loop.
+fn presented(
+    result: Result<(), glutin::error::Error>,
+    context_lost: &mut bool,
+) -> Result<Presented, GlWindowError> {
+    match result {
+        Ok(()) => Ok(Presented::Shown),
+        Err(err) if is_context_loss(&err) => {
+            log::warn!("Lost the context, recreating it: {err}");
+            *context_lost = true;
+            Ok(Presented::ContextLost)
+        }
+        Err(err) => Err(GlWindowError::Context(err)),
+    }
+}
+
+// Hand the new context to a renderer that outlived the old one.
+fn context_replaced<R: AppRenderer>(renderer: &mut R, gl: gl::Gl, lost: bool, size: SurfaceSize) {
+    if lost {
+        renderer.on_context_lost(gl);
+    } else {
+        renderer.context_recreated(gl);
+    }
+    renderer.surface_resized(size);
+}
+
+// How to go on after an error, or the error to exit the loop with and return from `run`.
+fn route_error(policy: &mut ErrorPolicy, err: GlWindowError) -> Result<AppControl, GlWindowError> {
+    match policy {
+        ErrorPolicy::ReturnErr => Err(err),
+        ErrorPolicy::Panic => panic!("{err}"),
+        ErrorPolicy::Callback(callback) => Ok(callback(err)),
+    }
+}
+
+/// What happens to errors while the event loop runs, from glwindow itself or returned by the
+/// handler, see [`Window::set_error_policy`].
+pub enum ErrorPolicy {
+    /// Exit the event loop and return the error from [`Window::run`].
+    ReturnErr,
+    /// Panic with the error message.
+    Panic,
+    /// Pass the error to a callback, which decides how to go on, e.g. with
+    /// [`AppControl::Continue`] to keep running. After some errors, like failing to create the
+    /// context, there is nothing to show though.
+    Callback(Box<dyn FnMut(GlWindowError) -> AppControl>),
+}
+
+/// How the event loop schedules frames, see [`Window::set_control_flow`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopMode {
+    /// Draw continuously, as fast as the present mode allows.
+    #[default]
+    Poll,
+    /// Only draw when a redraw was requested, e.g. through [`GlWindowHandle::request_redraw`],
+    /// or the platform needs the window contents again after being uncovered or resized. The
+    /// first frame is always drawn.
+    Wait,
+    /// Draw every given interval, and on request like [`LoopMode::Wait`].
+    WaitUntil(Duration),
+}
+
+/// Whether frames are drawn all the time or only when needed, see [`Window::set_redraw_mode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RedrawMode {
+    /// Draw frame after frame, for games and animations.
+    #[default]
+    Continuous,
+    /// Only draw when the platform asks for it or after a redraw request, and sleep in
+    /// between. This is the right choice for tools and editors that only update on input.
+    OnDemand,
+}
+
+/// Size of the window surface, in physical pixels and in logical units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SurfaceSize {
+    pub physical: PhysicalSize<u32>,
+    /// The physical size divided by the scale factor.
+    pub logical: LogicalSize<f64>,
+    pub scale_factor: f64,
+}
+
+impl SurfaceSize {
+    pub fn new(physical: PhysicalSize<u32>, scale_factor: f64) -> Self {
+        Self {
+            physical,
+            logical: physical.to_logical(scale_factor),
+            scale_factor,
+        }
+    }
+}
+
+pub trait AppRenderer {
+    type AppState;
+
+    /// Draw a frame. Called continuously, except on Wayland where frames are drawn when the
+    /// compositor is ready for the next one, which also means not at all while the window is
+    /// hidden.
+    ///
+    /// Animations need the time since the last frame, which [`AppRenderer::draw_with_ctx`]
+    /// gets in [`FrameCtx::dt`], zero for the first frame.
+    fn draw(&self, _app_state: &mut Self::AppState) {}
+    /// Draw a frame and tell the loop how to continue, e.g. to exit after a fatal GL error.
+    /// Calls [`AppRenderer::draw`] and continues by default.
+    fn draw_frame(&self, app_state: &mut Self::AppState) -> AppControl {
+        self.draw(app_state);
+        AppControl::Continue
+    }
+    /// Draw a frame with the window, surface size and frame time at hand. This is what the
+    /// loop calls, the recommended method to implement for new renderers. Forwards the app
+    /// state to [`AppRenderer::draw_frame`] by default.
+    ///
+    /// An error, e.g. for `GL_OUT_OF_MEMORY`, is handled like one returned by the event
+    /// handler, by default it ends the app and is returned from [`Window::run`].
+    fn draw_with_ctx(
+        &self,
+        ctx: FrameCtx<'_, Self::AppState>,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        Ok(self.draw_frame(ctx.app_state))
+    }
+    /// Advance the simulation by one fixed step of `dt`, see [`Window::set_fixed_timestep`].
+    /// Called before drawing as often as needed to catch up with the time passed, the frame
+    /// then gets [`FrameCtx::alpha`] to interpolate between the last two steps.
+    fn update(&mut self, _app_state: &mut Self::AppState, _dt: Duration) {}
+    /// Called once right after the renderer was created and before the first frame, with the
+    /// size of the surface in physical pixels, to set up viewports and projections.
+    fn init(&mut self, _width: i32, _height: i32) {}
+    /// Called once when the app exits, while the context is still current. This is the only
+    /// safe place to delete buffers, textures and shaders with `glDelete*`, afterwards the
+    /// context is gone.
+    fn shutdown(&mut self) {}
+    fn resize(&mut self, _width: i32, _height: i32) {}
+    /// Like [`AppRenderer::resize`], but with the logical size and scale factor as well. By
+    /// default this forwards the physical size to `resize`.
+    fn surface_resized(&mut self, size: SurfaceSize) {
+        self.resize(size.physical.width as i32, size.physical.height as i32);
+    }
+    /// Called whenever the window gains or loses keyboard focus, e.g. to throttle expensive
+    /// effects while in the background.
+    fn focus_changed(&mut self, _focused: bool) {}
+    /// Called when the window gets minimized or fully covered, and when it's visible again.
+    /// `draw` isn't called while the window is not visible.
+    fn visibility_changed(&mut self, _visible: bool) {}
+    /// Called when the context was replaced, e.g. for [`AppControl::RecreateSurface`]. Every
+    /// GL object of the old context is gone, so everything has to be uploaded again with the
+    /// new functions. The new surface size follows through `surface_resized`.
+    fn context_recreated(&mut self, _gl: gl::Gl) {}
+    /// Called when the context was lost, e.g. after a driver reset or a switch to another GPU,
+    /// and replaced with a new one. Like in [`AppRenderer::context_recreated`], which it calls
+    /// by default, everything has to be uploaded again.
+    fn on_context_lost(&mut self, gl: gl::Gl) {
+        self.context_recreated(gl);
+    }
+}
+
+/// A renderer [`Window::run`] can create by itself, from nothing but the GL functions.
+/// Renderers created by the factory passed to [`Window::run_with`] don't need this.
+pub trait AppRendererNew: AppRenderer + Sized {
+    /// Create the renderer once the GL functions are loaded, with the context current.
+    fn new(gl: gl::Gl) -> Self;
+}
+
+impl<S> AppRenderer for Box<dyn AppRenderer<AppState = S>> {
+    type AppState = S;
+
+    fn draw(&self, app_state: &mut S) {
+        (**self).draw(app_state)
+    }
+
+    fn draw_frame(&self, app_state: &mut S) -> AppControl {
+        (**self).draw_frame(app_state)
+    }
+
+    fn draw_with_ctx(&self, ctx: FrameCtx<'_, S>) -> Result<AppControl, Box<dyn Error>> {
+        (**self).draw_with_ctx(ctx)
+    }
+
+    fn resize(&mut self, width: i32, height: i32) {
+        (**self).resize(width, height)
+    }
+
+    fn surface_resized(&mut self, size: SurfaceSize) {
+        (**self).surface_resized(size)
+    }
+
+    fn focus_changed(&mut self, focused: bool) {
+        (**self).focus_changed(focused)
+    }
+
+    fn visibility_changed(&mut self, visible: bool) {
+        (**self).visibility_changed(visible)
+    }
+
+    fn context_recreated(&mut self, gl: gl::Gl) {
+        (**self).context_recreated(gl)
+    }
+
+    fn on_context_lost(&mut self, gl: gl::Gl) {
+        (**self).on_context_lost(gl)
+    }
+
+    fn update(&mut self, app_state: &mut S, dt: Duration) {
+        (**self).update(app_state, dt)
+    }
+
+    fn init(&mut self, width: i32, height: i32) {
+        (**self).init(width, height)
+    }
+
+    fn shutdown(&mut self) {
+        (**self).shutdown()
+    }
+}
+
+/// Whether an event seen by [`AppEventHandler::pre_handle_event`] is processed further.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PreHandleResult {
+    /// Handle the event as usual.
+    #[default]
+    Pass,
+    /// Drop the event, neither glwindow nor the other handler methods see it.
+    Consume,
+}
+
+pub enum AppControl {
+    Continue,
+    Exit,
+    /// Draw a frame soon, for [`LoopMode::Wait`] after the state changed. Any number of
+    /// requests before that frame result in a single one.
+    Redraw,
+    /// Switch to borderless fullscreen on the monitor of [`Window::set_monitor`] or the one the
+    /// window is on, or back to a window.
+    /// Exclusive fullscreen instead after [`Window::set_fullscreen_exclusive`].
+    SetFullscreen(bool),
+    /// Switch between fullscreen and a window, e.g. for a key press.
+    ToggleFullscreen,
+    /// Move the window to the monitor at `index` of [`WindowControls::available_monitors`],
+    /// like [`Window::set_monitor`] does before it's created, e.g. from a settings menu.
+    SetMonitor(usize),
+    /// Stop the clock behind [`FrameCtx::dt`] and [`FrameCtx::uptime`], e.g. while a pause menu
+    /// is open. Frames are still drawn and input is still handled, `dt` is zero until
+    /// [`AppControl::ResumeClock`].
+    PauseClock,
+    /// Start the clock again where it was paused.
+    ResumeClock,
+    /// Change which of the close, minimize and maximize buttons are available.
+    SetEnabledButtons(WindowButtons),
+    /// Show the progress of a long running job in the taskbar, from `0.0` to `1.0`. `None`
+    /// removes the indicator again.
+    ///
+    /// Only supported on Windows.
+    SetTaskbarProgress(Option<f32>),
+    /// Switch to a new framebuffer format, which recreates the window, surface and context.
+    /// The renderer is told through [`AppRenderer::context_recreated`].
+    RecreateSurface(FormatOptions),
+    /// Ask for a new inner size of the window in physical pixels, like for a "reset size"
+    /// button. The platform may adjust or ignore it.
+    RequestInnerSize((u32, u32)),
+    /// Switch the present mode, e.g. to turn vsync on or off from a settings menu. This only
+    /// changes the swap interval, the surface stays. [`WindowControls::present_mode`] tells
+    /// what was applied; when the swap interval was rejected, frames are limited to the refresh
+    /// rate instead unless the mode is [`PresentMode::Immediate`].
+    SetPresentMode(PresentMode),
+    /// Limit how small the window can be resized, in physical pixels. `None` removes the limit.
+    SetMinSize(Option<(u32, u32)>),
+    /// Limit how large the window can be resized, in physical pixels. `None` removes the limit.
+    SetMaxSize(Option<(u32, u32)>),
+    /// Keep the cursor inside part of the window while it's focused, by moving it back whenever
+    /// it leaves. Where the cursor can't be moved, it's confined to the whole window instead.
+    /// `None` releases it again.
+    ConfineCursorToRect(Option<Rect>),
+    /// Save the window contents as a PNG file after drawing the next frame. The outcome is
+    /// reported to [`AppEventHandler::handle_screenshot_saved`].
+    #[cfg(feature = "screenshot")]
+    SaveScreenshot(PathBuf),
+    /// Save every `n`th frame to numbered PNG files in a directory, which is created when
+    /// missing, until [`AppControl::StopRecording`]. The files are written on a worker thread;
+    /// drawing waits for it when it falls behind.
+    ///
+    /// Needs OpenGL ES 3. Any running recording is stopped first.
+    #[cfg(feature = "screenshot")]
+    StartRecording(PathBuf, u32),
+    /// Stop the recording and wait for the remaining files to be written, then report to
+    /// [`AppEventHandler::handle_recording_stopped`].
+    #[cfg(feature = "screenshot")]
+    StopRecording,
+}
+
+pub trait AppEventHandler {
+    type AppState;
+    /// Called for the window events, after glwindow processed them. `Resized` arrives once the
+    /// surface has the new size, and with a zero size when the window got minimized, which
+    /// leaves the surface as is.
+    fn handle_event(
+        &mut self,
+        app_state: &mut Self::AppState,
+        event: WindowEvent,
+    ) -> Result<AppControl, Box<dyn Error>>;
+
+    /// Called for every window event before glwindow processes it, e.g. resizing the surface
+    /// on `Resized`, and before it's passed to [`AppEventHandler::handle_window_event`].
+    /// Passes everything by default.
+    ///
+    /// Consuming an event skips all of that, so consuming `Resized` leaves the surface at the
+    /// old size and consuming `RedrawRequested` skips drawing that frame.
+    fn pre_handle_event(
+        &mut self,
+        _app_state: &mut Self::AppState,
+        _event: &WindowEvent,
+    ) -> PreHandleResult {
+        PreHandleResult::Pass
+    }
+
+    /// Like [`AppEventHandler::handle_event`], but with access to the window. Forwards to
+    /// `handle_event` by default.
+    fn handle_window_event(
+        &mut self,
+        app_state: &mut Self::AppState,
+        event: WindowEvent,
+        _window: &WindowControls,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        self.handle_event(app_state, event)
+    }
+
+    /// Called when the user asks to close the window, before `CloseRequested` is passed on.
+    /// Returning `false` ignores the request and the other methods don't see it, e.g. to ask
+    /// about saving unsaved work first and exit later with [`AppControl::Exit`].
+    fn on_close_requested(&mut self, _app_state: &mut Self::AppState) -> bool {
+        true
+    }
+
+    /// Called with the events sent through [`GlWindowHandle::send_event`], e.g. the results of
+    /// background work. Take the handle from [`Window::spawn`] before running the loop, and
+    /// get the event back with `event.downcast::<MyEvent>()`.
+    ///
+    /// The events aren't typed, any `Send` value can be sent. Return an error for one of a type
+    /// the handler doesn't expect, so a mismatch between sender and handler doesn't go unnoticed.
+    fn handle_user_event(
+        &mut self,
+        _app_state: &mut Self::AppState,
+        _event: Box<dyn Any + Send>,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        Ok(AppControl::Continue)
+    }
+
+    /// Called whenever the window was created, on Android again after each resume, for interop
+    /// with native APIs.
+    ///
+    /// The handles are only valid as long as the window exists, so until the app exits, or on
+    /// Android until the next suspend.
+    fn on_window_created(
+        &mut self,
+        _app_state: &mut Self::AppState,
+        _window_handle: RawWindowHandle,
+        _display_handle: RawDisplayHandle,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        Ok(AppControl::Continue)
+    }
+
+    /// Called when two fingers move apart or together on a touchscreen, or pinch on a trackpad.
+    /// `factor` is the change in zoom since the last call, above `1.0` for zooming in.
+    ///
+    /// The `Touch` and `PinchGesture` events are still passed to the other methods too.
+    fn handle_pinch_zoom(
+        &mut self,
+        _app_state: &mut Self::AppState,
+        _factor: f64,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        Ok(AppControl::Continue)
+    }
+
+    /// Called when two fingers move together on a touchscreen or trackpad, with the distance
+    /// since the last call in physical pixels.
+    fn handle_pan(
+        &mut self,
+        _app_state: &mut Self::AppState,
+        _delta: (f64, f64),
+    ) -> Result<AppControl, Box<dyn Error>> {
+        Ok(AppControl::Continue)
+    }
+
+    /// Called when the shortcut bound to `id` with [`Window::bind_action`] was pressed while the
+    /// window has focus. The key event itself isn't passed to the other methods.
+    fn handle_action(
+        &mut self,
+        _app_state: &mut Self::AppState,
+        _id: u32,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        Ok(AppControl::Continue)
+    }
+
+    /// Called when the global hotkey registered under `id` was pressed, whether the window has
+    /// focus or not.
+    #[cfg(feature = "global-hotkey")]
+    fn handle_global_hotkey(
+        &mut self,
+        _app_state: &mut Self::AppState,
+        _id: u32,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        Ok(AppControl::Continue)
+    }
+
+    /// Called with the outcome of [`AppControl::SaveScreenshot`]. By default a failure is
+    /// returned as is, which ends the app.
+    #[cfg(feature = "screenshot")]
+    fn handle_screenshot_saved(
+        &mut self,
+        _app_state: &mut Self::AppState,
+        _path: &Path,
+        result: Result<(), Box<dyn Error>>,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        result.map(|()| AppControl::Continue)
+    }
+
+    /// Called once a recording ended, or failed to start, with whether all frames were saved.
+    /// By default a failure is returned as is, which ends the app.
+    #[cfg(feature = "screenshot")]
+    fn handle_recording_stopped(
+        &mut self,
+        _app_state: &mut Self::AppState,
+        _dir: &Path,
+        result: Result<(), Box<dyn Error>>,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        result.map(|()| AppControl::Continue)
+    }
+}
+
+impl<S> AppEventHandler for fn(&mut S, WindowEvent) -> Result<AppControl, Box<dyn Error>> {
+    type AppState = S;
+    fn handle_event(
+        &mut self,
+        app_state: &mut Self::AppState,
+        event: WindowEvent,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        self(app_state, event)
+    }
+}
+
+impl<S> AppEventHandler for Box<dyn AppEventHandler<AppState = S>> {
+    type AppState = S;
+
+    fn handle_event(
+        &mut self,
+        app_state: &mut S,
+        event: WindowEvent,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        (**self).handle_event(app_state, event)
+    }
+
+    fn pre_handle_event(&mut self, app_state: &mut S, event: &WindowEvent) -> PreHandleResult {
+        (**self).pre_handle_event(app_state, event)
+    }
+
+    fn handle_window_event(
+        &mut self,
+        app_state: &mut S,
+        event: WindowEvent,
+        window: &WindowControls,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        (**self).handle_window_event(app_state, event, window)
+    }
+
+    fn handle_user_event(
+        &mut self,
+        app_state: &mut S,
+        event: Box<dyn Any + Send>,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        (**self).handle_user_event(app_state, event)
+    }
+
+    fn on_window_created(
+        &mut self,
+        app_state: &mut S,
+        window_handle: RawWindowHandle,
+        display_handle: RawDisplayHandle,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        (**self).on_window_created(app_state, window_handle, display_handle)
+    }
+
+    fn handle_pinch_zoom(
+        &mut self,
+        app_state: &mut S,
+        factor: f64,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        (**self).handle_pinch_zoom(app_state, factor)
+    }
+
+    fn handle_pan(
+        &mut self,
+        app_state: &mut S,
+        delta: (f64, f64),
+    ) -> Result<AppControl, Box<dyn Error>> {
+        (**self).handle_pan(app_state, delta)
+    }
+
+    fn handle_action(&mut self, app_state: &mut S, id: u32) -> Result<AppControl, Box<dyn Error>> {
+        (**self).handle_action(app_state, id)
+    }
+
+    fn on_close_requested(&mut self, app_state: &mut S) -> bool {
+        (**self).on_close_requested(app_state)
+    }
+
+    #[cfg(feature = "global-hotkey")]
+    fn handle_global_hotkey(
+        &mut self,
+        app_state: &mut S,
+        id: u32,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        (**self).handle_global_hotkey(app_state, id)
+    }
+
+    #[cfg(feature = "screenshot")]
+    fn handle_screenshot_saved(
+        &mut self,
+        app_state: &mut S,
+        path: &Path,
+        result: Result<(), Box<dyn Error>>,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        (**self).handle_screenshot_saved(app_state, path, result)
+    }
+
+    #[cfg(feature = "screenshot")]
+    fn handle_recording_stopped(
+        &mut self,
+        app_state: &mut S,
+        dir: &Path,
+        result: Result<(), Box<dyn Error>>,
+    ) -> Result<AppControl, Box<dyn Error>> {
+        (**self).handle_recording_stopped(app_state, dir, result)
+    }
+}
+
+pub type HandleFn<S> =
+    for<'a> fn(&'a mut S, WindowEvent) -> Result<AppControl, Box<dyn std::error::Error + 'static>>;
+
+type SurfaceAttributesHook =
+    Box<dyn Fn(SurfaceAttributesBuilder<WindowSurface>) -> SurfaceAttributesBuilder<WindowSurface>>;
+
+type ConfigTemplateHook = Box<dyn FnOnce(ConfigTemplateBuilder) -> ConfigTemplateBuilder>;
+
+type ConfigPicker = Box<dyn Fn(Box<dyn Iterator<Item = Config> + '_>) -> Option<Config>>;
 
 struct WindowInformation {
     pub transparent: bool,
+    pub require_transparency: bool,
+    pub blur: bool,
+    pub sticky: bool,
     pub fullscreen: bool,
+    // Width, height and refresh rate in millihertz, matched against the video modes whenever
+    // the window is created.
+    pub exclusive_mode: Option<(u32, u32, u32)>,
+    // Index into the available monitors.
+    pub monitor: Option<usize>,
+    pub position: Option<(i32, i32)>,
+    pub maximized: bool,
     pub resizable: bool,
+    pub enabled_buttons: WindowButtons,
     pub size: Option<(usize, usize)>,
+    pub min_size: Option<(u32, u32)>,
+    pub max_size: Option<(u32, u32)>,
     pub title: String,
-    pub icon: Option<Icon>,
+    // Raw RGBA data and dimensions, checked by `Window::validate`.
+    pub icon: Option<(Vec<u8>, usize, usize)>,
     pub cursor_visible: bool,
     pub cursor_grabbed: bool,
+    pub mouselook: bool,
+    pub initial_cursor_position: Option<(f64, f64)>,
+    pub loop_mode: LoopMode,
+    pub auto_request_redraw: bool,
+    pub idle_fps: Option<u32>,
+    pub idle_delay: Duration,
+    pub power_aware: bool,
+    pub finish_on_suspend: bool,
+    pub draw_while_occluded: bool,
+    pub gesture_thresholds: GestureThresholds,
+    pub internal_resolution: Option<(u32, u32)>,
+    pub aspect_ratio: Option<(u32, u32)>,
+    pub pixel_perfect: bool,
+    pub clear_color: Option<[f32; 4]>,
+    pub terminate_display_on_exit: bool,
+    pub allow_legacy_fallback: bool,
+    pub gl_version: Option<(GlApi, u8, u8)>,
+    pub format: FormatOptions,
+    pub surface_attributes: Option<SurfaceAttributesHook>,
+    pub config_template: Option<ConfigTemplateHook>,
+    pub config_picker: Option<ConfigPicker>,
+    pub error_policy: ErrorPolicy,
+    pub present_mode: PresentMode,
+    pub fixed_timestep: Option<Duration>,
+    // Vertical blanks to wait for per frame with vsync.
+    pub vblanks: NonZeroU32,
+    pub actions: ActionBindings,
+    #[cfg(feature = "global-hotkey")]
+    pub global_hotkeys: Vec<(u32, GlobalHotkey)>,
 }
 
 pub struct Window<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>> {
     window_info: WindowInformation,
+    renderer_init: Option<RendererFactory<R>>,
     _s: std::marker::PhantomData<S>,
     _h: std::marker::PhantomData<H>,
     _r: std::marker::PhantomData<R>,
@@ -412,14 +2455,53 @@ impl<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>> Window<S
         Window {
             window_info: WindowInformation {
                 transparent: true,
+                require_transparency: false,
+                blur: false,
+                sticky: false,
                 fullscreen: false,
+                exclusive_mode: None,
+                monitor: None,
+                position: None,
+                maximized: false,
                 resizable: true,
+                enabled_buttons: WindowButtons::all(),
                 size: None,
+                min_size: None,
+                max_size: None,
                 title: "".to_string(),
                 icon: None,
                 cursor_visible: true,
                 cursor_grabbed: false,
+                mouselook: false,
+                initial_cursor_position: None,
+                loop_mode: LoopMode::Poll,
+                auto_request_redraw: true,
+                idle_fps: None,
+                idle_delay: Duration::from_secs(2),
+                power_aware: false,
+                finish_on_suspend: true,
+                draw_while_occluded: false,
+                gesture_thresholds: GestureThresholds::default(),
+                internal_resolution: None,
+                aspect_ratio: None,
+                pixel_perfect: false,
+                clear_color: None,
+                terminate_display_on_exit: true,
+                allow_legacy_fallback: true,
+                gl_version: None,
+                format: FormatOptions::default(),
+                surface_attributes: None,
+                config_template: None,
+                config_picker: None,
+                error_policy: ErrorPolicy::ReturnErr,
+                present_mode: PresentMode::Fifo,
+                fixed_timestep: None,
+                vblanks: NonZeroU32::MIN,
+                actions: ActionBindings::default(),
+                #[cfg(feature = "global-hotkey")]
+                global_hotkeys: Vec::new(),
             },
+            renderer_init: None,
             _s: std::marker::PhantomData,
             _h: std::marker::PhantomData,
             _r: std::marker::PhantomData,
@@ -431,29 +2513,117 @@ impl<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>> Window<S
         self
     }
 
+    /// Fail with [`GlWindowError::NoTransparentConfig`] when a transparent window can't get a
+    /// config that supports transparency, instead of ending up opaque.
+    pub fn require_transparency(mut self, require: bool) -> Window<S, H, R> {
+        self.window_info.require_transparency = require;
+        self
+    }
+
+    /// Blur the background behind the window (acrylic on Windows).
+    ///
+    /// Only visible through a transparent window, so `set_transparent(true)` is needed as well.
+    /// Platforms without a blur effect ignore it.
+    pub fn set_blur(mut self, blur: bool) -> Window<S, H, R> {
+        self.window_info.blur = blur;
+        self
+    }
+
+    /// Show the window on all virtual desktops, like for an overlay.
+    ///
+    /// Only X11 window managers support this, it's ignored on Wayland and other platforms.
+    pub fn set_sticky(mut self, sticky: bool) -> Window<S, H, R> {
+        self.window_info.sticky = sticky;
+        self
+    }
+
     pub fn set_fullscreen(mut self, fullscreen: bool) -> Window<S, H, R> {
         self.window_info.fullscreen = fullscreen;
         self
     }
 
+    /// Open the window on the monitor at `index` of the available ones, in its top-left corner or
+    /// fullscreen there. The primary monitor when there are fewer.
+    ///
+    /// Wayland doesn't let windows choose their position, only the fullscreen monitor.
+    pub fn set_monitor(mut self, index: usize) -> Window<S, H, R> {
+        self.window_info.monitor = Some(index);
+        self
+    }
+
+    /// Start in exclusive fullscreen, with the video mode closest to this size in physical
+    /// pixels and refresh rate, e.g. `60_000` for 60 Hz. The mode is taken from the monitor of
+    /// [`Window::set_monitor`] or the primary one, borderless fullscreen when it lists no modes.
+    ///
+    /// [`AppControl::SetFullscreen`] switches back to the same mode.
+    pub fn set_fullscreen_exclusive(
+        mut self,
+        width: u32,
+        height: u32,
+        refresh_millihertz: u32,
+    ) -> Window<S, H, R> {
+        self.window_info.fullscreen = true;
+        self.window_info.exclusive_mode = Some((width, height, refresh_millihertz));
+        self
+    }
+
+    /// Where to put the top-left corner of the window, in physical pixels on the virtual
+    /// desktop, e.g. to restore it from the last session. Relative to the monitor after
+    /// [`Window::set_monitor`].
+    ///
+    /// Wayland doesn't let windows choose their position.
+    pub fn set_position(mut self, position: (i32, i32)) -> Window<S, H, R> {
+        self.window_info.position = Some(position);
+        self
+    }
+
+    pub fn set_maximized(mut self, maximized: bool) -> Window<S, H, R> {
+        self.window_info.maximized = maximized;
+        self
+    }
+
     pub fn set_resizable(mut self, resizable: bool) -> Window<S, H, R> {
         self.window_info.resizable = resizable;
         self
     }
 
+    /// Choose which of the close, minimize and maximize buttons are available.
+    ///
+    /// Platforms without control over the window buttons ignore this.
+    pub fn set_enabled_buttons(mut self, buttons: WindowButtons) -> Window<S, H, R> {
+        self.window_info.enabled_buttons = buttons;
+        self
+    }
+
     pub fn set_size(mut self, size: (usize, usize)) -> Window<S, H, R> {
         self.window_info.size = Some(size);
         self
     }
 
+    /// Limit how small the window can be resized, in physical pixels. Change it while running
+    /// with [`AppControl::SetMinSize`].
+    pub fn set_min_size(mut self, size: (u32, u32)) -> Window<S, H, R> {
+        self.window_info.min_size = Some(size);
+        self
+    }
+
+    /// Limit how large the window can be resized, in physical pixels. Change it while running
+    /// with [`AppControl::SetMaxSize`].
+    pub fn set_max_size(mut self, size: (u32, u32)) -> Window<S, H, R> {
+        self.window_info.max_size = Some(size);
+        self
+    }
+
     pub fn set_title(mut self, title: &str) -> Window<S, H, R> {
         self.window_info.title = title.to_string();
         self
     }
 
+    /// Use `data` as the window icon, RGBA with 8 bits per channel and `width` by `height`
+    /// pixels. A buffer of any other length makes `run` fail with
+    /// [`GlWindowError::InvalidIcon`], see [`Window::validate`] to check it earlier.
     pub fn set_icon(mut self, data: &[u8], width: usize, height: usize) -> Window<S, H, R> {
-        self.window_info.icon =
-            Some(Icon::from_rgba(data.to_vec(), width as u32, height as u32).unwrap());
+        self.window_info.icon = Some((data.to_vec(), width, height));
         self
     }
 
@@ -467,21 +2637,557 @@ impl<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>> Window<S
         self
     }
 
-    pub fn run(self, state: S, handler: H) -> Result<(), Box<dyn Error>> {
-        let event_loop = EventLoop::new().unwrap();
+    /// Move the cursor to `position`, in physical pixels from the top left corner of the
+    /// window, once the window is created. Some platforms, like Wayland, don't allow this.
+    pub fn set_initial_cursor_position(mut self, position: (f64, f64)) -> Window<S, H, R> {
+        self.window_info.initial_cursor_position = Some(position);
+        self
+    }
+
+    /// Lock the cursor in place and hide it from the start, for first person camera controls.
+    ///
+    /// Platforms that can't lock the cursor confine it to the window instead. Turning it off
+    /// again releases and shows the cursor.
+    pub fn set_mouselook(mut self, mouselook: bool) -> Window<S, H, R> {
+        self.window_info.mouselook = mouselook;
+        self.window_info.cursor_grabbed = mouselook;
+        self.window_info.cursor_visible = !mouselook;
+        self
+    }
+
+    /// How far fingers on a touchscreen have to move before
+    /// [`AppEventHandler::handle_pinch_zoom`] and [`AppEventHandler::handle_pan`] are called.
+    pub fn set_gesture_thresholds(mut self, thresholds: GestureThresholds) -> Window<S, H, R> {
+        self.window_info.gesture_thresholds = thresholds;
+        self
+    }
+
+    /// How errors are handled while running, [`ErrorPolicy::ReturnErr`] by default. Errors
+    /// before the event loop starts are always returned.
+    pub fn set_error_policy(mut self, policy: ErrorPolicy) -> Window<S, H, R> {
+        self.window_info.error_policy = policy;
+        self
+    }
+
+    /// When frames are drawn, [`LoopMode::Poll`] (continuously) by default.
+    ///
+    /// [`Window::set_idle_fps`] and [`Window::set_power_aware`] only limit the frame rate of
+    /// `Poll`, the other modes already draw only as often as asked for. On Wayland every mode
+    /// additionally waits for the compositor to be ready for the next frame.
+    pub fn set_control_flow(mut self, mode: LoopMode) -> Window<S, H, R> {
+        self.window_info.loop_mode = mode;
+        self
+    }
+
+    /// Draw continuously or on demand, [`RedrawMode::Continuous`] by default. This is a
+    /// shorthand for [`LoopMode::Poll`] and [`LoopMode::Wait`] with
+    /// [`Window::set_control_flow`], see there for more.
+    ///
+    /// On demand, a frame is drawn for [`WindowEvent::RedrawRequested`], [`AppControl::Redraw`]
+    /// and [`GlWindowHandle::request_redraw`].
+    pub fn set_redraw_mode(mut self, mode: RedrawMode) -> Window<S, H, R> {
+        self.window_info.loop_mode = match mode {
+            RedrawMode::Continuous => LoopMode::Poll,
+            RedrawMode::OnDemand => LoopMode::Wait,
+        };
+        self
+    }
+
+    /// Ask for `samples` per pixel of multisampling, or the closest lower count when that
+    /// isn't available. `0` and `1` ask for no multisampling. By default the config with the
+    /// most samples is picked, which can be needlessly expensive on integrated GPUs.
+    pub fn set_msaa(mut self, samples: u8) -> Window<S, H, R> {
+        self.window_info.format.samples = Some(if samples <= 1 { 0 } else { samples });
+        self
+    }
+
+    /// Call [`AppRenderer::update`] every `step` of time, e.g. 1/60 of a second, independent of
+    /// the frame rate. Off by default, a zero `step` turns it off again.
+    ///
+    /// A frame runs at most a few updates, after a stall the simulation falls behind instead
+    /// of running ever more updates to catch up.
+    pub fn set_fixed_timestep(mut self, step: Duration) -> Window<S, H, R> {
+        self.window_info.fixed_timestep = (!step.is_zero()).then_some(step);
+        self
+    }
+
+    /// Whether the event loop asks for new frames by itself, on by default. Turned off, frames
+    /// are only drawn on [`AppControl::Redraw`], [`GlWindowHandle::request_redraw`] or when the
+    /// platform asks for one, whatever the [`LoopMode`]. The first frame is still drawn.
+    ///
+    /// Requesting frames faster than the display refreshes doesn't speed anything up with
+    /// vsync, the swap blocks until the next vertical blank.
+    pub fn set_auto_request_redraw(mut self, auto: bool) -> Window<S, H, R> {
+        self.window_info.auto_request_redraw = auto;
+        self
+    }
+
+    /// Lower the frame rate to `fps` while there is no user input, to save power on scenes
+    /// that only change in response to the user. Any input returns to full speed immediately.
+    pub fn set_idle_fps(mut self, fps: Option<u32>) -> Window<S, H, R> {
+        self.window_info.idle_fps = fps;
+        self
+    }
+
+    /// How long there has to be no input before the idle frame rate kicks in, 2 seconds by
+    /// default.
+    pub fn set_idle_delay(mut self, delay: Duration) -> Window<S, H, R> {
+        self.window_info.idle_delay = delay;
+        self
+    }
+
+    /// Cap the frame rate to half the refresh rate while running on battery, off by default.
+    /// The power source is checked again every few seconds, so plugging in or out takes
+    /// effect at runtime.
+    ///
+    /// Detected on Linux and Windows, elsewhere the machine always counts as plugged in.
+    pub fn set_power_aware(mut self, power_aware: bool) -> Window<S, H, R> {
+        self.window_info.power_aware = power_aware;
+        self
+    }
+
+    /// Keep drawing while the window is hidden behind others or otherwise not visible, off by
+    /// default, e.g. for apps capturing their frames. Only a minimized window always stops,
+    /// it has no surface to draw into.
+    ///
+    /// Not every platform reports occlusion, there frames go on anyway.
+    pub fn set_draw_while_occluded(mut self, draw: bool) -> Window<S, H, R> {
+        self.window_info.draw_while_occluded = draw;
+        self
+    }
+
+    /// Wait for all GL commands to complete before the surface goes away on suspend, on by
+    /// default. Only Android suspends apps.
+    pub fn set_finish_on_suspend(mut self, finish: bool) -> Window<S, H, R> {
+        self.window_info.finish_on_suspend = finish;
+        self
+    }
+
+    /// Keep the window at this ratio of width to height while it's resized, e.g. `(16, 9)`.
+    /// `None` lets it take any shape, the default. Not kept while fullscreen or maximized.
+    ///
+    /// Each resize is answered with a request for the corrected size, so the window can briefly
+    /// be off while dragging, and window managers that place windows themselves may refuse it.
+    pub fn set_aspect_ratio(mut self, ratio: Option<(u32, u32)>) -> Window<S, H, R> {
+        self.window_info.aspect_ratio = ratio;
+        self
+    }
+
+    /// Render at a fixed resolution, which is then scaled into the window preserving the aspect
+    /// ratio, with black bars filling the rest.
+    ///
+    /// The renderer gets this size through `resize` and is never told about the window size.
+    /// It draws into an offscreen framebuffer, so a renderer binding its own framebuffers has to
+    /// restore the one bound when `draw` was called. That needs OpenGL ES 3 or OpenGL 3, on
+    /// older contexts it fails with [`GlWindowError::OffscreenFramebuffer`].
+    pub fn set_internal_resolution(mut self, size: (u32, u32)) -> Window<S, H, R> {
+        self.window_info.internal_resolution = Some(size);
+        self
+    }
+
+    /// Only scale the internal resolution by whole numbers, with nearest filtering, so pixel art
+    /// stays crisp. Uses the largest factor that fits the window. Has no effect without
+    /// [`Window::set_internal_resolution`].
+    pub fn set_pixel_perfect(mut self, pixel_perfect: bool) -> Window<S, H, R> {
+        self.window_info.pixel_perfect = pixel_perfect;
+        self
+    }
+
+    /// Clear the color buffer to `[r, g, b, a]` before every `draw`, off by default. Takes
+    /// straight alpha, for a transparent window the clear goes through
+    /// [`gl_clear_transparent`].
+    pub fn set_clear_color(mut self, color: Option<[f32; 4]>) -> Window<S, H, R> {
+        self.window_info.clear_color = color;
+        self
+    }
+
+    /// Adjust the template configs are picked from, e.g. to ask for depth and stencil buffers.
+    /// The builder passed in asks for an alpha channel, and for transparency if the window is
+    /// transparent.
+    pub fn set_config_template(
+        mut self,
+        template: impl FnOnce(ConfigTemplateBuilder) -> ConfigTemplateBuilder + 'static,
+    ) -> Window<S, H, R> {
+        self.window_info.config_template = Some(Box::new(template));
+        self
+    }
+
+    /// Pick the config from the ones matching the template yourself, e.g. the one with exactly
+    /// 4x MSAA and a 24 bit depth buffer. Returning `None` makes `run` fail with
+    /// [`GlWindowError::NoSuitableConfig`].
+    ///
+    /// This replaces the picking by [`Window::set_msaa`] and the sRGB and transparency
+    /// preferences. Without one, configs are picked by those and then like
+    /// [`gl_config_picker`].
+    pub fn set_config_picker(
+        mut self,
+        picker: impl Fn(Box<dyn Iterator<Item = Config> + '_>) -> Option<Config> + 'static,
+    ) -> Window<S, H, R> {
+        self.window_info.config_picker = Some(Box::new(picker));
+        self
+    }
+
+    /// Adjust the attributes of every window surface before it's created, e.g. for single
+    /// buffering. The builder passed in has the sRGB setting of the format applied already.
+    pub fn set_surface_attributes(
+        mut self,
+        attributes: impl Fn(SurfaceAttributesBuilder<WindowSurface>) -> SurfaceAttributesBuilder<WindowSurface>
+            + 'static,
+    ) -> Window<S, H, R> {
+        self.window_info.surface_attributes = Some(Box::new(attributes));
+        self
+    }
+
+    /// How frames are presented, [`PresentMode::Fifo`] (vsync) by default. Modes the platform
+    /// doesn't support fall back to the closest one available, see
+    /// [`WindowControls::present_mode`] for what was applied.
+    pub fn set_present_mode(mut self, present_mode: PresentMode) -> Window<S, H, R> {
+        self.window_info.present_mode = present_mode;
+        self
+    }
+
+    /// Turn vsync off, on, or wait for several vertical blanks per frame. On by default.
+    ///
+    /// This sets the present mode to [`PresentMode::Immediate`] or [`PresentMode::Fifo`], and
+    /// the number of vertical blanks is kept for [`AppControl::SetPresentMode`] later.
+    pub fn set_vsync(mut self, vsync: Vsync) -> Window<S, H, R> {
+        let (present_mode, vblanks) = match vsync {
+            Vsync::Off => (PresentMode::Immediate, NonZeroU32::MIN),
+            Vsync::On => (PresentMode::Fifo, NonZeroU32::MIN),
+            Vsync::Every(vblanks) => (PresentMode::Fifo, vblanks),
+        };
+        self.window_info.present_mode = present_mode;
+        self.window_info.vblanks = vblanks;
+        self
+    }
+
+    /// Whether to terminate the EGL display when the app exits, on by default. This works
+    /// around crashes in the nvidia driver on Wayland, but keeps the display from being reused
+    /// for another window afterwards.
+    pub fn set_terminate_display_on_exit(mut self, terminate: bool) -> Window<S, H, R> {
+        self.window_info.terminate_display_on_exit = terminate;
+        self
+    }
+
+    /// Bind a shortcut to an action of the app, reported to [`AppEventHandler::handle_action`]
+    /// with `id` instead of as a key event. The modifiers have to match exactly, so
+    /// `Ctrl + S` and `Ctrl + Shift + S` can be bound to different actions.
+    ///
+    /// An action can have several shortcuts, but binding one shortcut to two actions makes
+    /// `run` fail with [`GlWindowError::ConflictingShortcut`].
+    pub fn bind_action(mut self, id: u32, shortcut: Shortcut) -> Window<S, H, R> {
+        self.window_info.actions.bind(id, shortcut);
+        self
+    }
+
+    /// The first shortcut bound to the action `id`, e.g. to show it in a menu.
+    pub fn shortcut_for(&self, id: u32) -> Option<Shortcut> {
+        self.window_info.actions.shortcut(id)
+    }
+
+    /// Register a system wide hotkey, reported to
+    /// [`AppEventHandler::handle_global_hotkey`] with `id` even while the window is unfocused.
+    ///
+    /// Only Windows and X11 support this, on other platforms `run` fails with
+    /// [`GlWindowError::HotkeysUnsupported`]. On X11 a combination grabbed by another client
+    /// is silently never reported.
+    #[cfg(feature = "global-hotkey")]
+    pub fn add_global_hotkey(mut self, id: u32, hotkey: GlobalHotkey) -> Window<S, H, R> {
+        self.window_info.global_hotkeys.push((id, hotkey));
+        self
+    }
+
+    /// Create a context of exactly this API and version, e.g. OpenGL 3.3 for the shaders of
+    /// the renderer. Desktop OpenGL from 3.2 on gets the core profile, older versions have
+    /// none. There is no fallback to other versions then, `run` fails with
+    /// [`GlWindowError::ContextCreation`] when the version isn't available.
+    ///
+    /// The bindings in [`gl`] are for OpenGL ES, or desktop OpenGL with the `gl46` feature, so
+    /// ask for the same API.
+    pub fn set_gl_version(mut self, api: GlApi, major: u8, minor: u8) -> Window<S, H, R> {
+        self.window_info.gl_version = Some((api, major, minor));
+        self
+    }
+
+    /// Whether to fall back to an OpenGL 2.1 context when neither a core OpenGL nor a GLES
+    /// context can be created, on by default. Renderers that can't work with 2.1 should turn
+    /// this off, so `run` fails with [`GlWindowError::ContextCreation`] instead.
+    pub fn set_allow_legacy_fallback(mut self, allow: bool) -> Window<S, H, R> {
+        self.window_info.allow_legacy_fallback = allow;
+        self
+    }
+
+    /// Check the builder options for mistakes, which `run` does as well before creating the
+    /// window.
+    pub fn validate(&self) -> Result<(), GlWindowError> {
+        let info = &self.window_info;
+
+        if let Some((rgba, width, height)) = &info.icon {
+            // Checked, a wrapped product could match the length by accident.
+            if width.checked_mul(*height).and_then(|n| n.checked_mul(4)) != Some(rgba.len()) {
+                return Err(GlWindowError::InvalidIcon {
+                    width: *width,
+                    height: *height,
+                    len: rgba.len(),
+                });
+            }
+        }
+
+        let sizes = [
+            info.size,
+            info.internal_resolution
+                .map(|(width, height)| (width as usize, height as usize)),
+            info.min_size
+                .map(|(width, height)| (width as usize, height as usize)),
+            info.max_size
+                .map(|(width, height)| (width as usize, height as usize)),
+            info.aspect_ratio
+                .map(|(width, height)| (width as usize, height as usize)),
+        ];
+        for (width, height) in sizes.into_iter().flatten() {
+            if width == 0 || height == 0 {
+                return Err(GlWindowError::InvalidSize { width, height });
+            }
+        }
+
+        if let (Some(min), Some(max)) = (info.min_size, info.max_size) {
+            if min.0 > max.0 || min.1 > max.1 {
+                return Err(GlWindowError::InvalidSizeLimits { min, max });
+            }
+        }
+
+        if let Some((shortcut, first, second)) = info.actions.conflict() {
+            return Err(GlWindowError::ConflictingShortcut {
+                shortcut,
+                actions: (first, second),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Like [`Window::run`], but the renderer is created by `renderer` instead of
+    /// [`AppRendererNew::new`], so it can capture whatever it needs beyond the GL functions. It
+    /// also gets the initial surface size.
+    ///
+    /// An existing `new` converts to `run_with(state, handler, |gl, _| MyRenderer::new(gl))`.
+    pub fn run_with(
+        self,
+        state: S,
+        handler: H,
+        renderer: impl FnOnce(gl::Gl, SurfaceSize) -> R + 'static,
+    ) -> Result<(), GlWindowError> {
+        self.spawn_with(state, handler, renderer)?.run()
+    }
+
+    /// Like [`Window::spawn`], with the renderer created by `renderer` as in
+    /// [`Window::run_with`].
+    pub fn spawn_with(
+        self,
+        state: S,
+        handler: H,
+        renderer: impl FnOnce(gl::Gl, SurfaceSize) -> R + 'static,
+    ) -> Result<WindowRunner<S, H, R>, GlWindowError> {
+        self.spawn_factory(state, handler, Box::new(renderer))
+    }
+
+    /// Draw `frames` frames as fast as possible and report how long they took, for performance
+    /// testing. The renderer is created like with [`Window::run_with`].
+    ///
+    /// This turns off vsync and any frame rate limits and exits after the last frame. On
+    /// Wayland the compositor still paces the frames.
+    pub fn run_benchmark(
+        mut self,
+        state: S,
+        handler: H,
+        renderer: impl FnOnce(gl::Gl, SurfaceSize) -> R + 'static,
+        frames: u32,
+    ) -> Result<BenchmarkReport, GlWindowError> {
+        self.window_info.present_mode = PresentMode::Immediate;
+        self.window_info.loop_mode = LoopMode::Poll;
+        self.window_info.idle_fps = None;
+        self.window_info.power_aware = false;
+
+        let mut runner = self.spawn_with(state, handler, renderer)?;
+        runner.app.benchmark = Some(Benchmark::new(frames));
+        runner
+            .event_loop
+            .run_app(&mut runner.app)
+            .map_err(GlWindowError::EventLoop)?;
+        runner.app.exit_state?;
+
+        Ok(runner.app.benchmark.take().unwrap().report())
+    }
+
+    fn spawn_factory(
+        mut self,
+        state: S,
+        handler: H,
+        renderer: RendererFactory<R>,
+    ) -> Result<WindowRunner<S, H, R>, GlWindowError> {
+        self.validate()?;
+
+        let event_loop = EventLoop::<LoopEvent>::with_user_event()
+            .build()
+            .map_err(GlWindowError::EventLoop)?;
 
-        let template = ConfigTemplateBuilder::new()
+        let mut template = ConfigTemplateBuilder::new()
             .with_alpha_size(8)
-            .with_transparency(cfg!(cgl_backend));
+            .with_transparency(self.window_info.transparent);
+        if let Some(config_template) = self.window_info.config_template.take() {
+            template = config_template(template);
+        }
+
+        #[allow(unused_mut)]
+        let mut app = App::<S, H, R>::new(
+            template,
+            self.window_info,
+            renderer,
+            state,
+            handler,
+            event_loop.create_proxy(),
+        );
+        #[cfg(feature = "global-hotkey")]
+        if !app.window_info.global_hotkeys.is_empty() {
+            let hotkeys = std::mem::take(&mut app.window_info.global_hotkeys);
+            let proxy = event_loop.create_proxy();
+            app.hotkeys = Some(hotkey::HotkeyListener::start(hotkeys, move || {
+                let _ = proxy.send_event(LoopEvent::Hotkey);
+            })?);
+        }
+
+        Ok(WindowRunner { event_loop, app })
+    }
+}
+
+impl<S, H: AppEventHandler<AppState = S>, R: AppRendererNew<AppState = S>> Window<S, H, R> {
+    pub fn run(self, state: S, handler: H) -> Result<(), GlWindowError> {
+        self.spawn(state, handler)?.run()
+    }
+
+    /// Set up the event loop without running it yet, so a [`GlWindowHandle`] can be taken out
+    /// first. [`WindowRunner::run`] then runs it like [`Window::run`].
+    pub fn spawn(self, state: S, handler: H) -> Result<WindowRunner<S, H, R>, GlWindowError> {
+        self.spawn_with(state, handler, |gl, _| R::new(gl))
+    }
+}
+
+/// A window with its event loop set up, from [`Window::spawn`].
+pub struct WindowRunner<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>> {
+    event_loop: EventLoop<LoopEvent>,
+    app: App<S, H, R>,
+}
+
+impl<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>> WindowRunner<S, H, R> {
+    /// A handle to control the window from other threads, see [`GlWindowHandle`].
+    pub fn handle(&self) -> GlWindowHandle {
+        GlWindowHandle {
+            proxy: self.event_loop.create_proxy(),
+        }
+    }
+
+    /// Run the event loop until the app exits.
+    pub fn run(mut self) -> Result<(), GlWindowError> {
+        self.event_loop
+            .run_app(&mut self.app)
+            .map_err(GlWindowError::EventLoop)?;
 
-        let display_builder = DisplayBuilder::new()
-            .with_window_attributes(Some(window_attributes(&self.window_info)));
+        self.app.exit_state
+    }
+
+    /// Run the event loop until the next frame was drawn, see [`WindowRunner::run_frames`].
+    #[cfg(any(
+        windows,
+        macos_platform,
+        android_platform,
+        x11_platform,
+        wayland_platform
+    ))]
+    pub fn step(&mut self) -> Result<bool, GlWindowError> {
+        self.run_frames(1)
+    }
+
+    /// Run the event loop until `frames` more frames were drawn and return, instead of running
+    /// it until the app exits. For tests driving a renderer frame by frame, e.g. to compare
+    /// screenshots against reference images.
+    ///
+    /// Every frame is requested explicitly, so this works with [`LoopMode::Wait`] too. Returns
+    /// whether the app is still running, its error if it failed.
+    ///
+    /// Nothing is drawn while the window is minimized, fully covered or, on Android,
+    /// suspended. Then this returns early with fewer frames drawn instead of waiting for the
+    /// window to come back.
+    #[cfg(any(
+        windows,
+        macos_platform,
+        android_platform,
+        x11_platform,
+        wayland_platform
+    ))]
+    pub fn run_frames(&mut self, frames: u64) -> Result<bool, GlWindowError> {
+        use winit::platform::pump_events::{EventLoopExtPumpEvents, PumpStatus};
+
+        let target = self.app.frame_count + frames;
+        while self.app.frame_count < target {
+            if let Some(GlState { window, .. }) = self.app.gl_state.as_ref() {
+                window.request_redraw();
+            }
+            let status = self
+                .event_loop
+                .pump_app_events(Some(Duration::ZERO), &mut self.app);
+            if let PumpStatus::Exit(_) = status {
+                std::mem::replace(&mut self.app.exit_state, Ok(()))?;
+                return Ok(false);
+            }
+            // Pumping again would only spin until the window is shown.
+            if self.app.gl_state.is_none() || self.app.hidden() {
+                break;
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// A [`Window`] with the handler and renderer behind trait objects, for when naming the
+/// concrete types is more trouble than static dispatch is worth.
+pub type DynWindow<S> =
+    Window<S, Box<dyn AppEventHandler<AppState = S>>, Box<dyn AppRenderer<AppState = S>>>;
+
+/// The [`WindowRunner`] of a [`DynWindow`].
+pub type DynWindowRunner<S> =
+    WindowRunner<S, Box<dyn AppEventHandler<AppState = S>>, Box<dyn AppRenderer<AppState = S>>>;
+
+impl<S> DynWindow<S> {
+    /// Create a window with a boxed renderer. `AppRendererNew::new` can't be called on a trait
+    /// object, so the renderer is created by `renderer` once the GL functions are loaded, like
+    /// with [`Window::run_with`].
+    pub fn new_dyn(
+        renderer: impl FnOnce(gl::Gl, SurfaceSize) -> Box<dyn AppRenderer<AppState = S>> + 'static,
+    ) -> Self {
+        let mut window = Self::new();
+        window.renderer_init = Some(Box::new(renderer));
+        window
+    }
 
-        let mut app =
-            App::<S, H, R>::new(template, self.window_info, display_builder, state, handler);
-        event_loop.run_app(&mut app)?;
+    /// Run with the renderer from [`DynWindow::new_dyn`], like [`Window::run`].
+    /// [`GlWindowError::NoRenderer`] without one.
+    pub fn run(
+        self,
+        state: S,
+        handler: Box<dyn AppEventHandler<AppState = S>>,
+    ) -> Result<(), GlWindowError> {
+        self.spawn(state, handler)?.run()
+    }
 
-        app.exit_state
+    /// Set up the event loop with the renderer from [`DynWindow::new_dyn`], like
+    /// [`Window::spawn`].
+    pub fn spawn(
+        mut self,
+        state: S,
+        handler: Box<dyn AppEventHandler<AppState = S>>,
+    ) -> Result<DynWindowRunner<S>, GlWindowError> {
+        let renderer = self.renderer_init.take().ok_or(GlWindowError::NoRenderer)?;
+        self.spawn_factory(state, handler, renderer)
     }
 }
 
@@ -492,3 +3198,169 @@ impl<S, H: AppEventHandler<AppState = S>, R: AppRenderer<AppState = S>> Default
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn icon_with_wrong_sized_buffer_is_rejected() {
+        let result = DynWindow::<()>::new().set_icon(&[0; 3], 2, 2).validate();
+        assert!(matches!(
+            result,
+            Err(GlWindowError::InvalidIcon {
+                width: 2,
+                height: 2,
+                len: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn min_size_larger_than_max_size_is_rejected() {
+        let result = DynWindow::<()>::new()
+            .set_min_size((800, 300))
+            .set_max_size((640, 480))
+            .validate();
+        assert!(matches!(
+            result,
+            Err(GlWindowError::InvalidSizeLimits {
+                min: (800, 300),
+                max: (640, 480)
+            })
+        ));
+    }
+
+    fn surface_error() -> GlWindowError {
+        GlWindowError::SurfaceCreation(ErrorKind::BadConfig.into())
+    }
+
+    #[test]
+    fn return_err_policy_passes_error_through() {
+        let routed = route_error(&mut ErrorPolicy::ReturnErr, surface_error());
+        assert!(matches!(routed, Err(GlWindowError::SurfaceCreation(err))
+            if err.error_kind() == ErrorKind::BadConfig));
+    }
+
+    #[test]
+    fn callback_policy_gets_the_error() {
+        let mut policy = ErrorPolicy::Callback(Box::new(|err| {
+            assert!(matches!(err, GlWindowError::SurfaceCreation(_)));
+            AppControl::Continue
+        }));
+        let routed = route_error(&mut policy, surface_error());
+        assert!(matches!(routed, Ok(AppControl::Continue)));
+    }
+
+    #[test]
+    fn context_loss_error_kinds() {
+        assert!(is_context_loss(&ErrorKind::ContextLost.into()));
+        assert!(is_context_loss(&ErrorKind::BadSurface.into()));
+        assert!(!is_context_loss(&ErrorKind::BadConfig.into()));
+        assert!(!is_context_loss(&ErrorKind::OutOfMemory.into()));
+    }
+
+    #[test]
+    fn core_profile_is_only_asked_for_from_opengl_3_2() {
+        let (name, api, profile) = pinned_context(GlApi::OpenGl, 2, 1);
+        assert_eq!(name, "OpenGL 2.1");
+        assert_eq!(api, ContextApi::OpenGl(Some(Version::new(2, 1))));
+        assert_eq!(profile, None);
+
+        let (name, _, profile) = pinned_context(GlApi::OpenGl, 3, 1);
+        assert_eq!(name, "OpenGL 3.1");
+        assert_eq!(profile, None);
+
+        let (name, _, profile) = pinned_context(GlApi::OpenGl, 3, 2);
+        assert_eq!(name, "OpenGL 3.2 core");
+        assert_eq!(profile, Some(GlProfile::Core));
+
+        let (name, api, profile) = pinned_context(GlApi::Gles, 3, 0);
+        assert_eq!(name, "OpenGL ES 3.0");
+        assert_eq!(api, ContextApi::Gles(Some(Version::new(3, 0))));
+        assert_eq!(profile, None);
+    }
+
+    #[derive(Default)]
+    struct ContextEvents {
+        lost: u32,
+        recreated: u32,
+        resized: u32,
+    }
+
+    impl AppRenderer for ContextEvents {
+        type AppState = ();
+
+        fn surface_resized(&mut self, _size: SurfaceSize) {
+            self.resized += 1;
+        }
+
+        fn context_recreated(&mut self, _gl: gl::Gl) {
+            self.recreated += 1;
+        }
+
+        fn on_context_lost(&mut self, _gl: gl::Gl) {
+            self.lost += 1;
+        }
+    }
+
+    fn unloaded_gl() -> gl::Gl {
+        gl::Gl::load_with(|_| std::ptr::null())
+    }
+
+    #[test]
+    fn simulated_context_loss_recreates_instead_of_exiting() {
+        let size = SurfaceSize::new(PhysicalSize::new(640, 480), 1.0);
+        let mut renderer = ContextEvents::default();
+        let mut context_lost = false;
+
+        // What `render_frame` does with the swap result, then `finish_resume` with the flag.
+        let outcome = presented(Err(ErrorKind::ContextLost.into()), &mut context_lost);
+        assert!(matches!(outcome, Ok(Presented::ContextLost)));
+        assert!(context_lost);
+        context_replaced(
+            &mut renderer,
+            unloaded_gl(),
+            std::mem::take(&mut context_lost),
+            size,
+        );
+        assert_eq!((renderer.lost, renderer.recreated), (1, 0));
+
+        // The next frame presents normally, and a later replacement is no loss.
+        let outcome = presented(Ok(()), &mut context_lost);
+        assert!(matches!(outcome, Ok(Presented::Shown)));
+        context_replaced(
+            &mut renderer,
+            unloaded_gl(),
+            std::mem::take(&mut context_lost),
+            size,
+        );
+        assert_eq!((renderer.lost, renderer.recreated), (1, 1));
+    }
+
+    #[test]
+    fn other_swap_errors_end_the_loop() {
+        let mut context_lost = false;
+        let outcome = presented(Err(ErrorKind::BadAccess.into()), &mut context_lost);
+        assert!(matches!(outcome, Err(GlWindowError::Context(_))));
+        assert!(!context_lost);
+    }
+
+    #[test]
+    fn lost_context_is_reported_to_the_renderer() {
+        let size = SurfaceSize::new(PhysicalSize::new(640, 480), 1.0);
+        let mut renderer = ContextEvents::default();
+
+        context_replaced(&mut renderer, unloaded_gl(), true, size);
+        assert_eq!(
+            (renderer.lost, renderer.recreated, renderer.resized),
+            (1, 0, 1)
+        );
+
+        context_replaced(&mut renderer, unloaded_gl(), false, size);
+        assert_eq!(
+            (renderer.lost, renderer.recreated, renderer.resized),
+            (1, 1, 2)
+        );
+    }
+}
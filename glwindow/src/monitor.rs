@@ -0,0 +1,60 @@
+use winit::dpi::{PhysicalPosition, PhysicalSize};
+use winit::monitor::{MonitorHandle, VideoModeHandle};
+
+/// Description of a monitor, detached from the platform handle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    pub name: Option<String>,
+    /// Position of the monitor's top-left corner on the virtual desktop.
+    pub position: PhysicalPosition<i32>,
+    pub size: PhysicalSize<u32>,
+    pub scale_factor: f64,
+    /// Refresh rate of the current mode, in millihertz.
+    pub refresh_rate_millihertz: Option<u32>,
+    /// The modes for exclusive fullscreen, see [`crate::Window::set_fullscreen_exclusive`].
+    pub video_modes: Vec<VideoModeInfo>,
+}
+
+/// A video mode a monitor supports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VideoModeInfo {
+    pub size: PhysicalSize<u32>,
+    pub bit_depth: u16,
+    pub refresh_rate_millihertz: u32,
+}
+
+impl From<&MonitorHandle> for MonitorInfo {
+    fn from(monitor: &MonitorHandle) -> Self {
+        Self {
+            name: monitor.name(),
+            position: monitor.position(),
+            size: monitor.size(),
+            scale_factor: monitor.scale_factor(),
+            refresh_rate_millihertz: monitor.refresh_rate_millihertz(),
+            video_modes: monitor
+                .video_modes()
+                .map(|mode| VideoModeInfo {
+                    size: mode.size(),
+                    bit_depth: mode.bit_depth(),
+                    refresh_rate_millihertz: mode.refresh_rate_millihertz(),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// The video mode of `monitor` closest to the requested one, by size first and refresh rate
+/// second, with the deepest color among equal ones.
+pub(crate) fn closest_video_mode(
+    monitor: &MonitorHandle,
+    (width, height, refresh_millihertz): (u32, u32, u32),
+) -> Option<VideoModeHandle> {
+    monitor.video_modes().min_by_key(|mode| {
+        let size = mode.size();
+        (
+            size.width.abs_diff(width) + size.height.abs_diff(height),
+            mode.refresh_rate_millihertz().abs_diff(refresh_millihertz),
+            std::cmp::Reverse(mode.bit_depth()),
+        )
+    })
+}
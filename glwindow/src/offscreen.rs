@@ -0,0 +1,174 @@
+// Framebuffer for rendering at a fixed internal resolution, which then gets scaled into the
+// window with black bars around it.
+
+use crate::gl;
+use crate::gl::types::{GLfloat, GLint, GLuint};
+use crate::GlWindowError;
+
+pub(crate) struct Offscreen {
+    framebuffer: GLuint,
+    color: GLuint,
+    depth_stencil: GLuint,
+    size: (u32, u32),
+    pixel_perfect: bool,
+}
+
+impl Offscreen {
+    /// Safety: the context the `gl` functions were loaded for must be current.
+    pub(crate) unsafe fn new(
+        gl: &gl::Gl,
+        size: (u32, u32),
+        pixel_perfect: bool,
+    ) -> Result<Self, GlWindowError> {
+        // Blitting between framebuffers needs OpenGL ES 3 or OpenGL 3, which the oldest fallback
+        // contexts lack.
+        if !gl.BlitFramebuffer.is_loaded() {
+            return Err(GlWindowError::OffscreenFramebuffer(
+                "scaling the image into the window needs OpenGL ES 3 or OpenGL 3".to_string(),
+            ));
+        }
+        let (width, height) = (size.0 as i32, size.1 as i32);
+
+        let mut color = 0;
+        gl.GenRenderbuffers(1, &mut color);
+        gl.BindRenderbuffer(gl::RENDERBUFFER, color);
+        gl.RenderbufferStorage(gl::RENDERBUFFER, gl::RGBA8, width, height);
+
+        let mut depth_stencil = 0;
+        gl.GenRenderbuffers(1, &mut depth_stencil);
+        gl.BindRenderbuffer(gl::RENDERBUFFER, depth_stencil);
+        gl.RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH24_STENCIL8, width, height);
+        gl.BindRenderbuffer(gl::RENDERBUFFER, 0);
+
+        let mut framebuffer = 0;
+        gl.GenFramebuffers(1, &mut framebuffer);
+        gl.BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+        gl.FramebufferRenderbuffer(
+            gl::FRAMEBUFFER,
+            gl::COLOR_ATTACHMENT0,
+            gl::RENDERBUFFER,
+            color,
+        );
+        gl.FramebufferRenderbuffer(
+            gl::FRAMEBUFFER,
+            gl::DEPTH_STENCIL_ATTACHMENT,
+            gl::RENDERBUFFER,
+            depth_stencil,
+        );
+        let status = gl.CheckFramebufferStatus(gl::FRAMEBUFFER);
+        gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+
+        let offscreen = Self {
+            framebuffer,
+            color,
+            depth_stencil,
+            size,
+            pixel_perfect,
+        };
+        if status != gl::FRAMEBUFFER_COMPLETE {
+            offscreen.delete(gl);
+            return Err(GlWindowError::OffscreenFramebuffer(format!(
+                "the framebuffer is incomplete, status {status:#x}"
+            )));
+        }
+        Ok(offscreen)
+    }
+
+    /// Make the offscreen framebuffer the target for the renderer.
+    pub(crate) unsafe fn bind(&self, gl: &gl::Gl) {
+        gl.BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+    }
+
+    /// Scale the rendered image into the window framebuffer, clearing the remaining area to
+    /// black.
+    pub(crate) unsafe fn present(&self, gl: &gl::Gl, window_size: (u32, u32)) {
+        let (x, y, width, height) = self.target_rect(window_size);
+        // Sharp pixels when scaling by whole numbers, smooth ones otherwise.
+        let filter = if self.pixel_perfect {
+            gl::NEAREST
+        } else {
+            gl::LINEAR
+        };
+
+        // Don't leak our clear color and scissor state into the renderer.
+        let mut clear_color: [GLfloat; 4] = [0.; 4];
+        gl.GetFloatv(gl::COLOR_CLEAR_VALUE, clear_color.as_mut_ptr());
+        let scissor = gl.IsEnabled(gl::SCISSOR_TEST) != 0;
+
+        gl.BindFramebuffer(gl::READ_FRAMEBUFFER, self.framebuffer);
+        gl.BindFramebuffer(gl::DRAW_FRAMEBUFFER, 0);
+        gl.Disable(gl::SCISSOR_TEST);
+        gl.ClearColor(0., 0., 0., 1.);
+        gl.Clear(gl::COLOR_BUFFER_BIT);
+        gl.BlitFramebuffer(
+            0,
+            0,
+            self.size.0 as GLint,
+            self.size.1 as GLint,
+            x,
+            y,
+            x + width,
+            y + height,
+            gl::COLOR_BUFFER_BIT,
+            filter,
+        );
+
+        if scissor {
+            gl.Enable(gl::SCISSOR_TEST);
+        }
+        let [r, g, b, a] = clear_color;
+        gl.ClearColor(r, g, b, a);
+    }
+
+    /// Where the image ends up in the window, as `(x, y, width, height)` from the bottom left.
+    pub(crate) fn target_rect(&self, window_size: (u32, u32)) -> (i32, i32, i32, i32) {
+        if self.pixel_perfect {
+            integer_letterbox(self.size, window_size)
+        } else {
+            letterbox(self.size, window_size)
+        }
+    }
+
+    pub(crate) unsafe fn delete(&self, gl: &gl::Gl) {
+        gl.DeleteFramebuffers(1, &self.framebuffer);
+        gl.DeleteRenderbuffers(1, &self.color);
+        gl.DeleteRenderbuffers(1, &self.depth_stencil);
+    }
+}
+
+/// The largest rectangle with the aspect ratio of `content` that fits centered into `target`, as
+/// `(x, y, width, height)`.
+pub(crate) fn letterbox(content: (u32, u32), target: (u32, u32)) -> (i32, i32, i32, i32) {
+    let scale = f64::min(
+        target.0 as f64 / content.0 as f64,
+        target.1 as f64 / content.1 as f64,
+    );
+    let width = ((content.0 as f64 * scale).round() as i32).min(target.0 as i32);
+    let height = ((content.1 as f64 * scale).round() as i32).min(target.1 as i32);
+
+    (
+        (target.0 as i32 - width) / 2,
+        (target.1 as i32 - height) / 2,
+        width,
+        height,
+    )
+}
+
+/// Like [`letterbox`], but only scaling by whole numbers. Falls back to [`letterbox`] when the
+/// target is too small to fit the content even once.
+pub(crate) fn integer_letterbox(content: (u32, u32), target: (u32, u32)) -> (i32, i32, i32, i32) {
+    let scale = u32::min(target.0 / content.0, target.1 / content.1);
+    if scale == 0 {
+        return letterbox(content, target);
+    }
+
+    let width = (content.0 * scale) as i32;
+    let height = (content.1 * scale) as i32;
+
+    (
+        (target.0 as i32 - width) / 2,
+        (target.1 as i32 - height) / 2,
+        width,
+        height,
+    )
+}
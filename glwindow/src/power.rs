@@ -0,0 +1,93 @@
+// Detecting whether the machine runs on battery, for `Window::set_power_aware`. Platforms
+// without a query count as being plugged in.
+
+use std::time::{Duration, Instant};
+
+use winit::window::Window;
+
+use crate::present;
+
+// How often to look at the power source again, it's a file read or syscall each time.
+const CHECK_INTERVAL: Duration = Duration::from_secs(10);
+
+pub(crate) struct PowerMonitor {
+    on_battery: bool,
+    next_check: Instant,
+}
+
+impl PowerMonitor {
+    pub(crate) fn new() -> Self {
+        Self {
+            on_battery: on_battery(),
+            next_check: Instant::now() + CHECK_INTERVAL,
+        }
+    }
+
+    /// The time between frames while on battery, half the refresh rate of the monitor `window`
+    /// is on. `None` when plugged in.
+    pub(crate) fn frame_interval(&mut self, window: &Window) -> Option<Duration> {
+        let now = Instant::now();
+        if now >= self.next_check {
+            self.on_battery = on_battery();
+            self.next_check = now + CHECK_INTERVAL;
+        }
+        if !self.on_battery {
+            return None;
+        }
+
+        Some(present::refresh_interval(window) * 2)
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn on_battery() -> bool {
+    use std::fs;
+
+    let Ok(supplies) = fs::read_dir("/sys/class/power_supply") else {
+        return false;
+    };
+
+    // Plugged in when any mains supply is online. Without one at all this is a desktop.
+    let mut has_mains = false;
+    for supply in supplies.flatten() {
+        let path = supply.path();
+        let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+        if kind.trim() != "Mains" {
+            continue;
+        }
+        has_mains = true;
+        let online = fs::read_to_string(path.join("online")).unwrap_or_default();
+        if online.trim() == "1" {
+            return false;
+        }
+    }
+    has_mains
+}
+
+#[cfg(windows)]
+fn on_battery() -> bool {
+    #[repr(C)]
+    #[derive(Default)]
+    struct SystemPowerStatus {
+        ac_line_status: u8,
+        battery_flag: u8,
+        battery_life_percent: u8,
+        system_status_flag: u8,
+        battery_life_time: u32,
+        battery_full_life_time: u32,
+    }
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetSystemPowerStatus(status: *mut SystemPowerStatus) -> i32;
+    }
+
+    let mut status = SystemPowerStatus::default();
+    // 0 is offline, 1 online and 255 unknown.
+    unsafe { GetSystemPowerStatus(&mut status) != 0 && status.ac_line_status == 0 }
+}
+
+#[cfg(not(any(target_os = "linux", windows)))]
+fn on_battery() -> bool {
+    false
+}
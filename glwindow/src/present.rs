@@ -0,0 +1,170 @@
+use std::num::NonZeroU32;
+use std::time::Duration;
+
+use glutin::context::PossiblyCurrentContext;
+use glutin::prelude::*;
+use glutin::surface::{Surface, SwapInterval, WindowSurface};
+use winit::window::Window;
+
+// Assumed when the monitor doesn't report its refresh rate.
+const DEFAULT_REFRESH_MILLIHERTZ: u32 = 60_000;
+
+/// How finished frames are handed to the display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PresentMode {
+    /// Wait for the vertical blank, no tearing. This is vsync.
+    #[default]
+    Fifo,
+    /// Like [`PresentMode::Fifo`], but a frame that misses the vertical blank is presented
+    /// right away and tears, instead of stalling for a whole interval. Also known as adaptive
+    /// vsync, it needs `EXT_swap_control_tear` on GLX or WGL and falls back to
+    /// [`PresentMode::Fifo`] elsewhere.
+    FifoRelaxed,
+    /// Present right away, lowest latency but frames can tear.
+    Immediate,
+    /// Present the newest frame at the vertical blank without blocking the renderer. OpenGL
+    /// can't express this, so it falls back to [`PresentMode::Fifo`] to avoid tearing.
+    Mailbox,
+}
+
+/// Whether to wait for the vertical blank, see [`crate::Window::set_vsync`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vsync {
+    /// Present right away, like [`PresentMode::Immediate`].
+    Off,
+    /// Wait for the next vertical blank, like [`PresentMode::Fifo`].
+    On,
+    /// Wait for `n` vertical blanks per frame, e.g. 2 for 30 frames per second on a 60 Hz
+    /// monitor.
+    Every(NonZeroU32),
+}
+
+/// The time between refreshes of the monitor `window` is on.
+pub(crate) fn refresh_interval(window: &Window) -> Duration {
+    let refresh = window
+        .current_monitor()
+        .and_then(|monitor| monitor.refresh_rate_millihertz())
+        .unwrap_or(DEFAULT_REFRESH_MILLIHERTZ);
+    Duration::from_secs_f64(1000. / refresh as f64)
+}
+
+/// Apply the present mode to the surface, returning the one that actually took effect.
+pub(crate) fn apply(
+    surface: &Surface<WindowSurface>,
+    context: &PossiblyCurrentContext,
+    mode: PresentMode,
+    vblanks: NonZeroU32,
+) -> Option<PresentMode> {
+    let interval = match mode {
+        PresentMode::Immediate => SwapInterval::DontWait,
+        _ => SwapInterval::Wait(vblanks),
+    };
+    if let Err(res) = surface.set_swap_interval(context, interval) {
+        log::warn!("Error setting vsync: {res:?}");
+        return None;
+    }
+
+    match mode {
+        PresentMode::Immediate => Some(PresentMode::Immediate),
+        // Regular vsync is already set up in case this fails.
+        PresentMode::FifoRelaxed if set_swap_tear(surface, context, vblanks) => {
+            Some(PresentMode::FifoRelaxed)
+        }
+        PresentMode::FifoRelaxed => {
+            log::warn!("Adaptive vsync isn't supported, using regular vsync");
+            Some(PresentMode::Fifo)
+        }
+        PresentMode::Fifo | PresentMode::Mailbox => Some(PresentMode::Fifo),
+    }
+}
+
+// `EXT_swap_control_tear` is enabled with a negative swap interval, which glutin can't express,
+// so the extension functions are called directly.
+#[allow(unused_variables)]
+fn set_swap_tear(
+    surface: &Surface<WindowSurface>,
+    context: &PossiblyCurrentContext,
+    vblanks: NonZeroU32,
+) -> bool {
+    use glutin::display::GetGlDisplay;
+
+    let interval = -(vblanks.get() as i32);
+
+    #[allow(unreachable_patterns)]
+    match context.display() {
+        #[cfg(glx_backend)]
+        glutin::display::Display::Glx(display) => {
+            use glutin::display::{AsRawDisplay, GetDisplayExtensions, RawDisplay};
+            use glutin::surface::{AsRawSurface, RawSurface};
+            use std::os::raw::{c_int, c_uint, c_ulong};
+
+            if !display.extensions().contains("GLX_EXT_swap_control_tear") {
+                return false;
+            }
+            #[allow(irrefutable_let_patterns)]
+            let (RawDisplay::Glx(raw_display), RawSurface::Glx(drawable)) =
+                (display.raw_display(), surface.raw_surface())
+            else {
+                return false;
+            };
+
+            let swap_interval = display.get_proc_address(proc_name(b"glXSwapIntervalEXT\0"));
+            let query_drawable = display.get_proc_address(proc_name(b"glXQueryDrawable\0"));
+            if swap_interval.is_null() || query_drawable.is_null() {
+                return false;
+            }
+            // A `GLXDrawable` is an XID, which is only 64 bits wide on LP64 targets.
+            let drawable = drawable as c_ulong;
+            unsafe {
+                let swap_interval: unsafe extern "C" fn(*const std::ffi::c_void, c_ulong, c_int) =
+                    std::mem::transmute(swap_interval);
+                let query_drawable: unsafe extern "C" fn(
+                    *const std::ffi::c_void,
+                    c_ulong,
+                    c_int,
+                    *mut c_uint,
+                ) = std::mem::transmute(query_drawable);
+                swap_interval(raw_display, drawable, interval);
+
+                // The call has no result, so ask the drawable what it got. A negative interval
+                // reads back as its magnitude with late swaps tearing.
+                let mut applied = 0;
+                let mut tears = 0;
+                query_drawable(raw_display, drawable, GLX_SWAP_INTERVAL_EXT, &mut applied);
+                query_drawable(raw_display, drawable, GLX_LATE_SWAPS_TEAR_EXT, &mut tears);
+                applied == vblanks.get() && tears == 1
+            }
+        }
+        #[cfg(wgl_backend)]
+        glutin::display::Display::Wgl(display) => {
+            use glutin::display::GetDisplayExtensions;
+
+            if !display.extensions().contains("WGL_EXT_swap_control_tear") {
+                return false;
+            }
+
+            let swap_interval = display.get_proc_address(proc_name(b"wglSwapIntervalEXT\0"));
+            if swap_interval.is_null() {
+                return false;
+            }
+            // Applies to the current context, which is the one we were given.
+            unsafe {
+                let swap_interval: unsafe extern "system" fn(i32) -> i32 =
+                    std::mem::transmute(swap_interval);
+                swap_interval(interval) != 0
+            }
+        }
+        _ => false,
+    }
+}
+
+#[cfg(glx_backend)]
+const GLX_SWAP_INTERVAL_EXT: std::os::raw::c_int = 0x20F1;
+#[cfg(glx_backend)]
+const GLX_LATE_SWAPS_TEAR_EXT: std::os::raw::c_int = 0x20F3;
+
+// C string literals need a newer compiler than the one supported.
+#[cfg(any(glx_backend, wgl_backend))]
+fn proc_name(name: &'static [u8]) -> &'static std::ffi::CStr {
+    std::ffi::CStr::from_bytes_with_nul(name).unwrap()
+}
@@ -0,0 +1,207 @@
+// Recording every nth frame to numbered PNG files, for `AppControl::StartRecording`.
+//
+// Frames are read back into two pixel buffer objects in turn, so the copy of one frame can
+// finish while the next is drawn, and only the frame before is mapped. Encoding happens on a
+// worker thread.
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::ptr;
+use std::sync::mpsc::{self, SyncSender};
+use std::thread::{self, JoinHandle};
+
+use crate::gl;
+use crate::gl::types::{GLint, GLsizeiptr, GLuint};
+use crate::screenshot;
+
+// Frames waiting for the worker. Drawing blocks when it falls this far behind, which bounds the
+// memory a slow disk can take up.
+const QUEUE_LEN: usize = 4;
+
+type WorkerResult = Result<(), Box<dyn Error + Send + Sync>>;
+
+struct Frame {
+    path: PathBuf,
+    size: (u32, u32),
+    pixels: Vec<u8>,
+}
+
+struct Readback {
+    path: PathBuf,
+    size: (u32, u32),
+}
+
+pub(crate) struct Recorder {
+    dir: PathBuf,
+    every_n: u32,
+    frames_seen: u64,
+    frames_saved: u64,
+    pbos: [GLuint; 2],
+    pbo_sizes: [(u32, u32); 2],
+    readbacks: [Option<Readback>; 2],
+    next: usize,
+    sender: Option<SyncSender<Frame>>,
+    worker: Option<JoinHandle<WorkerResult>>,
+}
+
+impl Recorder {
+    /// Safety: the context the `gl` functions were loaded for must be current.
+    pub(crate) unsafe fn start(
+        gl: &gl::Gl,
+        dir: PathBuf,
+        every_n: u32,
+    ) -> Result<Self, Box<dyn Error>> {
+        // Pixel buffer objects need OpenGL ES 3, which the oldest fallback context lacks.
+        if !gl.MapBufferRange.is_loaded() {
+            return Err("recording needs OpenGL ES 3 or later".into());
+        }
+        fs::create_dir_all(&dir)?;
+
+        let (sender, frames) = mpsc::sync_channel::<Frame>(QUEUE_LEN);
+        let worker = thread::spawn(move || -> WorkerResult {
+            for frame in frames {
+                screenshot::write_flipped(&frame.path, frame.size, &frame.pixels)?;
+            }
+            Ok(())
+        });
+
+        let mut pbos = [0; 2];
+        gl.GenBuffers(2, pbos.as_mut_ptr());
+
+        Ok(Self {
+            dir,
+            every_n: every_n.max(1),
+            frames_seen: 0,
+            frames_saved: 0,
+            pbos,
+            pbo_sizes: [(0, 0); 2],
+            readbacks: [None, None],
+            next: 0,
+            sender: Some(sender),
+            worker: Some(worker),
+        })
+    }
+
+    pub(crate) fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Called for every frame before swapping buffers, with the default framebuffer of `size`
+    /// holding the finished frame. Fails when the worker stopped because of an error.
+    ///
+    /// Safety: the context the `gl` functions were loaded for must be current.
+    pub(crate) unsafe fn capture(
+        &mut self,
+        gl: &gl::Gl,
+        size: (u32, u32),
+    ) -> Result<(), Box<dyn Error>> {
+        let frame = self.frames_seen;
+        self.frames_seen += 1;
+        if frame % self.every_n as u64 != 0 || size.0 == 0 || size.1 == 0 {
+            return Ok(());
+        }
+
+        let path = self.dir.join(format!("frame_{:06}.png", self.frames_saved));
+        self.frames_saved += 1;
+
+        let mut read_framebuffer = 0;
+        gl.GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut read_framebuffer);
+        let mut pack_buffer = 0;
+        gl.GetIntegerv(gl::PIXEL_PACK_BUFFER_BINDING, &mut pack_buffer);
+        let mut pack_alignment = 0;
+        gl.GetIntegerv(gl::PACK_ALIGNMENT, &mut pack_alignment);
+
+        let current = self.next;
+        gl.BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+        gl.BindBuffer(gl::PIXEL_PACK_BUFFER, self.pbos[current]);
+        gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
+        if self.pbo_sizes[current] != size {
+            gl.BufferData(
+                gl::PIXEL_PACK_BUFFER,
+                byte_len(size) as GLsizeiptr,
+                ptr::null(),
+                gl::STREAM_READ,
+            );
+            self.pbo_sizes[current] = size;
+        }
+        gl.ReadPixels(
+            0,
+            0,
+            size.0 as GLint,
+            size.1 as GLint,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            ptr::null_mut(),
+        );
+        self.readbacks[current] = Some(Readback { path, size });
+
+        // The other buffer was filled a captured frame ago, by now that copy is done.
+        self.next = 1 - current;
+        let result = self.flush(gl, self.next);
+
+        gl.PixelStorei(gl::PACK_ALIGNMENT, pack_alignment);
+        gl.BindBuffer(gl::PIXEL_PACK_BUFFER, pack_buffer as u32);
+        gl.BindFramebuffer(gl::READ_FRAMEBUFFER, read_framebuffer as u32);
+
+        result
+    }
+
+    /// Save the frames still in flight and wait for the worker, deleting the buffers.
+    ///
+    /// Safety: the context the `gl` functions were loaded for must be current.
+    pub(crate) unsafe fn stop(mut self, gl: &gl::Gl) -> Result<(), Box<dyn Error>> {
+        let mut pack_buffer = 0;
+        gl.GetIntegerv(gl::PIXEL_PACK_BUFFER_BINDING, &mut pack_buffer);
+
+        // Oldest first, to keep the files in order.
+        let mut result = self.flush(gl, self.next);
+        if result.is_ok() {
+            result = self.flush(gl, 1 - self.next);
+        }
+
+        gl.BindBuffer(gl::PIXEL_PACK_BUFFER, pack_buffer as u32);
+        gl.DeleteBuffers(2, self.pbos.as_ptr());
+
+        // Closing the queue ends the worker once it is empty.
+        self.sender = None;
+        let worker = self.worker.take().unwrap();
+        match worker.join() {
+            Ok(Ok(())) => result,
+            Ok(Err(e)) => Err(e),
+            Err(_) => Err("the recording worker panicked".into()),
+        }
+    }
+
+    // Hand the frame read into `pbo` to the worker, if there is one.
+    unsafe fn flush(&mut self, gl: &gl::Gl, pbo: usize) -> Result<(), Box<dyn Error>> {
+        let Some(Readback { path, size }) = self.readbacks[pbo].take() else {
+            return Ok(());
+        };
+
+        let len = byte_len(size);
+        gl.BindBuffer(gl::PIXEL_PACK_BUFFER, self.pbos[pbo]);
+        let mapped = gl.MapBufferRange(
+            gl::PIXEL_PACK_BUFFER,
+            0,
+            len as GLsizeiptr,
+            gl::MAP_READ_BIT,
+        );
+        if mapped.is_null() {
+            return Err("failed to map the recording buffer".into());
+        }
+        let pixels = std::slice::from_raw_parts(mapped as *const u8, len).to_vec();
+        gl.UnmapBuffer(gl::PIXEL_PACK_BUFFER);
+
+        let frame = Frame { path, size, pixels };
+        if self.sender.as_ref().unwrap().send(frame).is_err() {
+            // The worker hung up after failing to write a frame, `stop` returns its error.
+            return Err("the recording worker stopped".into());
+        }
+        Ok(())
+    }
+}
+
+fn byte_len(size: (u32, u32)) -> usize {
+    size.0 as usize * size.1 as usize * 4
+}
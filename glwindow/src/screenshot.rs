@@ -0,0 +1,99 @@
+// Saving the window framebuffer to a PNG file, for `AppControl::SaveScreenshot`.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::gl;
+use crate::gl::types::GLint;
+use crate::GlWindowError;
+
+// Upper bound for the pixels read back at once, large framebuffers are saved in strips of rows.
+const STRIP_BYTES: usize = 4 << 20;
+
+/// Read the default framebuffer of `size` pixels and write it to `path`, top row first.
+///
+/// Safety: the context the `gl` functions were loaded for must be current.
+pub(crate) unsafe fn save(
+    gl: &gl::Gl,
+    size: (u32, u32),
+    path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    let (width, height) = size;
+    if width == 0 || height == 0 {
+        return Err(GlWindowError::NoSurface.into());
+    }
+    let row_bytes = width as usize * 4;
+    let strip_rows = (STRIP_BYTES / row_bytes).clamp(1, height as usize);
+
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), width, height);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    let mut stream = writer.stream_writer()?;
+
+    // Keep the renderer's read state as it was.
+    let mut read_framebuffer = 0;
+    gl.GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut read_framebuffer);
+    let mut pack_buffer = 0;
+    gl.GetIntegerv(gl::PIXEL_PACK_BUFFER_BINDING, &mut pack_buffer);
+    let mut pack_alignment = 0;
+    gl.GetIntegerv(gl::PACK_ALIGNMENT, &mut pack_alignment);
+
+    gl.BindFramebuffer(gl::READ_FRAMEBUFFER, 0);
+    gl.BindBuffer(gl::PIXEL_PACK_BUFFER, 0);
+    gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
+
+    // GL rows start at the bottom, so read strips from the top down and flip each of them.
+    let mut strip = vec![0u8; strip_rows * row_bytes];
+    let mut result = Ok(());
+    let mut top = height as usize;
+    while top > 0 && result.is_ok() {
+        let rows = strip_rows.min(top);
+        let bottom = top - rows;
+        let strip = &mut strip[..rows * row_bytes];
+        gl.ReadPixels(
+            0,
+            bottom as GLint,
+            width as GLint,
+            rows as GLint,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            strip.as_mut_ptr() as *mut _,
+        );
+        result = strip
+            .chunks_exact(row_bytes)
+            .rev()
+            .try_for_each(|row| stream.write_all(row));
+        top = bottom;
+    }
+
+    gl.PixelStorei(gl::PACK_ALIGNMENT, pack_alignment);
+    gl.BindBuffer(gl::PIXEL_PACK_BUFFER, pack_buffer as u32);
+    gl.BindFramebuffer(gl::READ_FRAMEBUFFER, read_framebuffer as u32);
+
+    result?;
+    stream.finish()?;
+    writer.finish()?;
+    Ok(())
+}
+
+/// Write RGBA `pixels` of `size`, as read back from GL with the bottom row first, to `path`.
+pub(crate) fn write_flipped(
+    path: &Path,
+    size: (u32, u32),
+    pixels: &[u8],
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    let mut encoder = png::Encoder::new(BufWriter::new(File::create(path)?), size.0, size.1);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header()?;
+    let mut stream = writer.stream_writer()?;
+    for row in pixels.chunks_exact(size.0 as usize * 4).rev() {
+        stream.write_all(row)?;
+    }
+    stream.finish()?;
+    writer.finish()?;
+    Ok(())
+}
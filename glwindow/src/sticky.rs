@@ -0,0 +1,111 @@
+// Showing the window on all virtual desktops, for `Window::set_sticky`. Only X11 window managers
+// have a way to ask for this, through the EWMH hints.
+
+use winit::window::Window;
+
+/// Ask the window manager to show `window` on every desktop. Does nothing where that isn't
+/// supported.
+pub(crate) fn make_sticky(window: &Window) {
+    #[cfg(x11_platform)]
+    x11::make_sticky(window);
+    #[cfg(not(x11_platform))]
+    let _ = window;
+}
+
+#[cfg(x11_platform)]
+mod x11 {
+    use std::ffi::{c_char, c_long, c_ulong};
+    use std::mem;
+
+    use raw_window_handle::{HasDisplayHandle, HasWindowHandle, RawDisplayHandle, RawWindowHandle};
+    use winit::window::Window;
+    use x11_dl::xlib::{self, Xlib};
+
+    // `_NET_WM_DESKTOP` value for all desktops.
+    const ALL_DESKTOPS: c_ulong = 0xFFFFFFFF;
+    const NET_WM_STATE_ADD: c_long = 1;
+    // Marks the request as coming from an application rather than a pager.
+    const SOURCE_APPLICATION: c_long = 1;
+
+    pub(super) fn make_sticky(window: &Window) {
+        let (Ok(window_handle), Ok(display_handle)) =
+            (window.window_handle(), window.display_handle())
+        else {
+            return;
+        };
+        // Wayland has no such thing.
+        let (RawWindowHandle::Xlib(window_handle), RawDisplayHandle::Xlib(display_handle)) =
+            (window_handle.as_raw(), display_handle.as_raw())
+        else {
+            return;
+        };
+        let (Some(display), Ok(xlib)) = (display_handle.display, Xlib::open()) else {
+            return;
+        };
+
+        // winit's own connection, used from the thread it belongs to.
+        unsafe {
+            let display = display.as_ptr() as *mut xlib::Display;
+            let window = window_handle.window;
+            let atom = |name: &[u8]| {
+                (xlib.XInternAtom)(display, name.as_ptr() as *const c_char, xlib::False)
+            };
+            let desktop = atom(b"_NET_WM_DESKTOP\0");
+            let state = atom(b"_NET_WM_STATE\0");
+            let sticky = atom(b"_NET_WM_STATE_STICKY\0");
+
+            // The property is read when the window gets mapped, the messages are for window
+            // managers that mapped it already.
+            (xlib.XChangeProperty)(
+                display,
+                window,
+                desktop,
+                xlib::XA_CARDINAL,
+                32,
+                xlib::PropModeReplace,
+                &ALL_DESKTOPS as *const c_ulong as *const u8,
+                1,
+            );
+            send_message(
+                &xlib,
+                display,
+                window,
+                desktop,
+                [ALL_DESKTOPS as c_long, SOURCE_APPLICATION, 0, 0, 0],
+            );
+            send_message(
+                &xlib,
+                display,
+                window,
+                state,
+                [NET_WM_STATE_ADD, sticky as c_long, 0, SOURCE_APPLICATION, 0],
+            );
+            (xlib.XFlush)(display);
+        }
+    }
+
+    unsafe fn send_message(
+        xlib: &Xlib,
+        display: *mut xlib::Display,
+        window: c_ulong,
+        message_type: c_ulong,
+        data: [c_long; 5],
+    ) {
+        let mut event: xlib::XClientMessageEvent = mem::zeroed();
+        event.type_ = xlib::ClientMessage;
+        event.window = window;
+        event.message_type = message_type;
+        event.format = 32;
+        for (i, value) in data.into_iter().enumerate() {
+            event.data.set_long(i, value);
+        }
+        let mut event = xlib::XEvent::from(event);
+        (xlib.XSendEvent)(
+            display,
+            (xlib.XDefaultRootWindow)(display),
+            xlib::False,
+            xlib::SubstructureNotifyMask | xlib::SubstructureRedirectMask,
+            &mut event,
+        );
+    }
+}
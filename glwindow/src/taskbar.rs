@@ -0,0 +1,157 @@
+// Taskbar progress indicator through the ITaskbarList3 COM interface. Only Windows has one, on
+// every other platform setting the progress is a no-op.
+
+use winit::window::Window;
+
+#[cfg(windows)]
+pub(crate) use self::windows::TaskbarProgress;
+
+#[cfg(not(windows))]
+pub(crate) struct TaskbarProgress;
+
+#[cfg(not(windows))]
+impl TaskbarProgress {
+    pub(crate) fn new() -> Option<Self> {
+        None
+    }
+
+    pub(crate) fn set(&self, _window: &Window, _progress: Option<f32>) {}
+}
+
+#[cfg(windows)]
+mod windows {
+    use std::ffi::c_void;
+    use std::ptr;
+
+    use raw_window_handle::{HasWindowHandle, RawWindowHandle};
+    use winit::window::Window;
+
+    const CLSCTX_INPROC_SERVER: u32 = 0x1;
+    const COINIT_APARTMENTTHREADED: u32 = 0x2;
+    const TBPF_NOPROGRESS: i32 = 0x0;
+    // Resolution of the progress bar, the taskbar only takes integer values.
+    const PROGRESS_STEPS: u64 = 10_000;
+
+    #[repr(C)]
+    struct Guid {
+        data1: u32,
+        data2: u16,
+        data3: u16,
+        data4: [u8; 8],
+    }
+
+    // {56FDF344-FD6D-11d0-958A-006097C9A090}
+    const CLSID_TASKBAR_LIST: Guid = Guid {
+        data1: 0x56fdf344,
+        data2: 0xfd6d,
+        data3: 0x11d0,
+        data4: [0x95, 0x8a, 0x00, 0x60, 0x97, 0xc9, 0xa0, 0x90],
+    };
+
+    // {EA1AFB91-9E28-4B86-90E9-9E9F8A5EEFAF}
+    const IID_ITASKBAR_LIST3: Guid = Guid {
+        data1: 0xea1afb91,
+        data2: 0x9e28,
+        data3: 0x4b86,
+        data4: [0x90, 0xe9, 0x9e, 0x9f, 0x8a, 0x5e, 0xef, 0xaf],
+    };
+
+    #[repr(C)]
+    struct ITaskbarList3 {
+        vtbl: *const ITaskbarList3Vtbl,
+    }
+
+    // Only the methods we call are typed, the rest just keep the slots in the right place.
+    #[repr(C)]
+    struct ITaskbarList3Vtbl {
+        query_interface: usize,
+        add_ref: usize,
+        release: unsafe extern "system" fn(*mut ITaskbarList3) -> u32,
+        hr_init: unsafe extern "system" fn(*mut ITaskbarList3) -> i32,
+        add_tab: usize,
+        delete_tab: usize,
+        activate_tab: usize,
+        set_active_alt: usize,
+        mark_fullscreen_window: usize,
+        set_progress_value: unsafe extern "system" fn(*mut ITaskbarList3, isize, u64, u64) -> i32,
+        set_progress_state: unsafe extern "system" fn(*mut ITaskbarList3, isize, i32) -> i32,
+    }
+
+    #[link(name = "ole32")]
+    extern "system" {
+        fn CoInitializeEx(reserved: *mut c_void, co_init: u32) -> i32;
+        fn CoUninitialize();
+        fn CoCreateInstance(
+            clsid: *const Guid,
+            outer: *mut c_void,
+            cls_context: u32,
+            iid: *const Guid,
+            out: *mut *mut c_void,
+        ) -> i32;
+    }
+
+    pub(crate) struct TaskbarProgress {
+        list: *mut ITaskbarList3,
+        uninitialize: bool,
+    }
+
+    impl TaskbarProgress {
+        pub(crate) fn new() -> Option<Self> {
+            unsafe {
+                // winit usually initialized COM on this thread already, in which case this only
+                // bumps the reference count.
+                let uninitialize = CoInitializeEx(ptr::null_mut(), COINIT_APARTMENTTHREADED) >= 0;
+
+                let mut list: *mut ITaskbarList3 = ptr::null_mut();
+                let hr = CoCreateInstance(
+                    &CLSID_TASKBAR_LIST,
+                    ptr::null_mut(),
+                    CLSCTX_INPROC_SERVER,
+                    &IID_ITASKBAR_LIST3,
+                    &mut list as *mut _ as *mut *mut c_void,
+                );
+
+                let progress = Self { list, uninitialize };
+                if hr < 0 || list.is_null() || ((*(*list).vtbl).hr_init)(list) < 0 {
+                    return None;
+                }
+
+                Some(progress)
+            }
+        }
+
+        pub(crate) fn set(&self, window: &Window, progress: Option<f32>) {
+            let hwnd = match window.window_handle().map(|handle| handle.as_raw()) {
+                Ok(RawWindowHandle::Win32(handle)) => handle.hwnd.get(),
+                _ => return,
+            };
+
+            unsafe {
+                let vtbl = &*(*self.list).vtbl;
+                match progress {
+                    Some(progress) => {
+                        let done = (progress.clamp(0., 1.) * PROGRESS_STEPS as f32) as u64;
+                        // Setting a value implicitly switches the bar into the normal state.
+                        (vtbl.set_progress_value)(self.list, hwnd, done, PROGRESS_STEPS);
+                    }
+                    None => {
+                        (vtbl.set_progress_state)(self.list, hwnd, TBPF_NOPROGRESS);
+                    }
+                }
+            }
+        }
+    }
+
+    impl Drop for TaskbarProgress {
+        fn drop(&mut self) {
+            unsafe {
+                if !self.list.is_null() {
+                    ((*(*self.list).vtbl).release)(self.list);
+                }
+                if self.uninitialize {
+                    CoUninitialize();
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+
+use winit::dpi::PhysicalPosition;
+use winit::event::{Force, Touch, TouchPhase, WindowEvent};
+
+/// A finger currently on the touchscreen.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TouchPoint {
+    /// Identifies the finger until it's lifted, after that the id can be reused.
+    pub id: u64,
+    /// Where the finger is now, in physical pixels.
+    pub position: PhysicalPosition<f64>,
+    /// Where the finger first touched down.
+    pub start_position: PhysicalPosition<f64>,
+    /// [`TouchPhase::Started`] until the first move, [`TouchPhase::Moved`] after.
+    pub phase: TouchPhase,
+    /// Pressure, on hardware that reports it.
+    pub force: Option<Force>,
+}
+
+/// Keeps track of the fingers on the touchscreen, fed from the window events like
+/// [`crate::InputState`].
+///
+/// The renderer gets the state as of drawing each frame in [`crate::FrameCtx::touch`]. The
+/// `Touch` events themselves still reach the handler as they are.
+#[derive(Debug, Clone, Default)]
+pub struct TouchState {
+    touches: HashMap<u64, TouchPoint>,
+}
+
+impl TouchState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Update the state from an event, call this for every event the handler receives.
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::Touch(Touch {
+                id,
+                location,
+                phase,
+                force,
+                ..
+            }) => match phase {
+                TouchPhase::Started => {
+                    self.touches.insert(
+                        *id,
+                        TouchPoint {
+                            id: *id,
+                            position: *location,
+                            start_position: *location,
+                            phase: TouchPhase::Started,
+                            force: *force,
+                        },
+                    );
+                }
+                TouchPhase::Moved => {
+                    if let Some(touch) = self.touches.get_mut(id) {
+                        touch.position = *location;
+                        touch.phase = TouchPhase::Moved;
+                        touch.force = *force;
+                    }
+                }
+                TouchPhase::Ended | TouchPhase::Cancelled => {
+                    self.touches.remove(id);
+                }
+            },
+            // Like with keys, lifting a finger while unfocused is never delivered.
+            WindowEvent::Focused(false) => self.touches.clear(),
+            _ => (),
+        }
+    }
+
+    pub fn touch(&self, id: u64) -> Option<&TouchPoint> {
+        self.touches.get(&id)
+    }
+
+    /// The fingers currently down, in no particular order.
+    pub fn touches(&self) -> impl Iterator<Item = &TouchPoint> + '_ {
+        self.touches.values()
+    }
+
+    pub fn touch_count(&self) -> usize {
+        self.touches.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use winit::event::DeviceId;
+
+    use super::*;
+
+    fn touch(id: u64, phase: TouchPhase, x: f64, y: f64) -> WindowEvent {
+        WindowEvent::Touch(Touch {
+            device_id: DeviceId::dummy(),
+            phase,
+            location: PhysicalPosition::new(x, y),
+            force: None,
+            id,
+        })
+    }
+
+    fn active_ids(state: &TouchState) -> Vec<u64> {
+        let mut ids: Vec<u64> = state.touches().map(|touch| touch.id).collect();
+        ids.sort();
+        ids
+    }
+
+    #[test]
+    fn tracks_fingers_from_start_to_end() {
+        let mut state = TouchState::new();
+
+        state.handle_event(&touch(1, TouchPhase::Started, 10., 20.));
+        state.handle_event(&touch(2, TouchPhase::Started, 100., 200.));
+        assert_eq!(active_ids(&state), [1, 2]);
+
+        state.handle_event(&touch(1, TouchPhase::Moved, 15., 25.));
+        let moved = state.touch(1).unwrap();
+        assert_eq!(moved.position, PhysicalPosition::new(15., 25.));
+        assert_eq!(moved.start_position, PhysicalPosition::new(10., 20.));
+        assert_eq!(moved.phase, TouchPhase::Moved);
+        assert_eq!(state.touch(2).unwrap().phase, TouchPhase::Started);
+
+        state.handle_event(&touch(1, TouchPhase::Ended, 15., 25.));
+        assert_eq!(active_ids(&state), [2]);
+
+        state.handle_event(&touch(2, TouchPhase::Ended, 100., 200.));
+        assert_eq!(state.touch_count(), 0);
+    }
+}
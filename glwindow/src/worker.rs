@@ -0,0 +1,88 @@
+// A second context sharing objects with the window's one, for worker threads.
+
+use std::ffi::CString;
+
+use glutin::config::GetGlConfig;
+use glutin::context::{
+    ContextAttributesBuilder, GlContext, NotCurrentContext, PossiblyCurrentContext,
+};
+use glutin::display::{GetGlDisplay, GlDisplay};
+use glutin::prelude::*;
+use raw_window_handle::RawWindowHandle;
+
+use crate::{gl, GlWindowError};
+
+/// An OpenGL context sharing textures, buffers and other objects with the window's context, to
+/// upload them on a worker thread while the window keeps drawing.
+///
+/// Send it to the worker thread and make it current there with
+/// [`WorkerContext::make_current`]. A context can only be current on one thread at a time, so
+/// it has to be made not current again before sending it elsewhere. Objects created on the
+/// worker are only guaranteed to be complete for the window after a fence or `glFinish` there.
+pub struct WorkerContext {
+    context: NotCurrentContext,
+}
+
+impl WorkerContext {
+    pub(crate) fn new(
+        main: &PossiblyCurrentContext,
+        window_handle: Option<RawWindowHandle>,
+    ) -> Result<Self, GlWindowError> {
+        let config = main.config();
+        let attributes = ContextAttributesBuilder::new()
+            .with_sharing(main)
+            .with_context_api(main.context_api())
+            .build(window_handle);
+        let context = unsafe { config.display().create_context(&config, &attributes) }
+            .map_err(GlWindowError::WorkerContext)?;
+        Ok(Self { context })
+    }
+
+    /// Make the context current on the calling thread, without a surface to draw to.
+    pub fn make_current(self) -> Result<CurrentWorkerContext, glutin::error::Error> {
+        let context = match self.context {
+            #[cfg(egl_backend)]
+            NotCurrentContext::Egl(context) => {
+                PossiblyCurrentContext::Egl(context.make_current_surfaceless()?)
+            }
+            #[cfg(glx_backend)]
+            NotCurrentContext::Glx(context) => {
+                PossiblyCurrentContext::Glx(context.make_current_surfaceless()?)
+            }
+            #[cfg(wgl_backend)]
+            NotCurrentContext::Wgl(context) => {
+                PossiblyCurrentContext::Wgl(context.make_current_surfaceless()?)
+            }
+            #[cfg(cgl_backend)]
+            NotCurrentContext::Cgl(context) => {
+                PossiblyCurrentContext::Cgl(context.make_current_surfaceless()?)
+            }
+        };
+
+        let display = context.display();
+        let gl = gl::Gl::load_with(|symbol| {
+            let symbol = CString::new(symbol).unwrap();
+            display.get_proc_address(symbol.as_c_str()).cast()
+        });
+        Ok(CurrentWorkerContext { context, gl })
+    }
+}
+
+/// A [`WorkerContext`] current on this thread.
+pub struct CurrentWorkerContext {
+    context: PossiblyCurrentContext,
+    gl: gl::Gl,
+}
+
+impl CurrentWorkerContext {
+    /// The GL functions for this context, only to be called on the thread it's current on.
+    pub fn gl(&self) -> &gl::Gl {
+        &self.gl
+    }
+
+    /// Release the context from this thread, so it can be sent to another one.
+    pub fn make_not_current(self) -> Result<WorkerContext, glutin::error::Error> {
+        let context = self.context.make_not_current()?;
+        Ok(WorkerContext { context })
+    }
+}